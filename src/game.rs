@@ -1,245 +1,4880 @@
-use core::panic;
 /**
  * Command-line Space Invaders, personal introduction to systems-level programming with Rust.
  * @author Maxylan (https://github.com/Maxylan)
  * @license MIT
  */
-use std::{collections::LinkedList, error::Error, time};
+use std::{collections::VecDeque, io, io::Write, path::PathBuf, sync::atomic::{AtomicBool, Ordering}, time};
+use serde::{Deserialize, Serialize};
 use termsize::Size;
-use rand::Rng;
+use unicode_width::UnicodeWidthStr;
+use entities::Bounded as _;
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
 
+mod ai;
+mod benchmark;
+mod cli;
+mod colors;
+mod difficulty;
 mod entities;
+mod events;
+// Only the gamepad's mapping (and its tests) is built without the 'gamepad' feature.
+#[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+mod gamepad;
+mod glyphs;
+mod input;
+mod inspector;
+mod leaderboard;
+mod menu;
+mod profile;
+mod render;
+mod replay;
+mod save;
+mod scoring;
+mod sprites;
+mod viewport;
+mod stats;
+
+/** Everything the player (human or AI) can ask the game to do on a given frame. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    /** Center the ship on the given column (mouse input). */
+    MoveTo(u16),
+    Fire,
+    /** Pause the game, or resume it when already paused. */
+    Pause,
+    /** Move the pause menu's highlight up an entry. */
+    MenuUp,
+    /** Move the pause menu's highlight down an entry. */
+    MenuDown,
+    /** Pick the highlighted pause menu entry. */
+    Confirm,
+    /** Open or close the inspector (debug overlay). */
+    ToggleInspector,
+    /** Scale the arena up / down a step, within the terminal. */
+    ScaleUp,
+    ScaleDown,
+    /** Switch to the next color theme (see `colors::THEMES`), wrapping back around to the first. */
+    CycleTheme,
+    /** Continue the saved game, from the title screen (see `save`). */
+    Continue,
+    /** Type a letter (of the player's initials), or take the latest one back. */
+    Type(char),
+    Erase,
+    /** Move the inspector's cursor. */
+    CursorUp,
+    CursorDown,
+    CursorLeft,
+    CursorRight,
+    /** Leave the game, handled by the loop in `start()` rather than the simulation. */
+    Quit,
+}
+
+/** Phase of the game as a whole, see `GameState::advance_phase` for how one leads to another. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Phase {
+    /** Title screen, waiting for the player to fire (or confirm) to start, or to continue a saved game. */
+    Title,
+    /** Counting down to the start of the game, frames left. The ship can move, but everything else is frozen. */
+    Countdown(u16),
+    Playing,
+    /** Frozen mid-game, showing the pause menu. */
+    Paused(menu::PauseMenu),
+    /**
+     * Breather after a wave was cleared (tallying its bonus), frames left until the next one spawns.
+     * The ship can move, but nothing else.
+     */
+    WaveCleared(u16),
+    /** The player picked `Quit` from the pause menu, the loop in `start()` exits on seeing it. */
+    Quit,
+    GameOver,
+    /** Every wave of a finite campaign (`total_waves`) was cleared. */
+    Won,
+}
+
+/** Score interval at which a "bullet time" slowdown is awarded. */
+const SLOWDOWN_SCORE_EVERY: u32 = 250;
+/** How much slower enemies and stars move during a slowdown. */
+const SLOWDOWN_FACTOR: u64 = 2;
+/** Frames the formation takes to descend a row, after bouncing off an edge. */
+const DESCENT_FRAMES: u16 = 4;
+/** Frame times kept for the `--profile` report. */
+const FRAME_TIME_SAMPLES: usize = 4096;
+/** Headlines kept on the HUD's ticker, older ones scrolling out. */
+const TICKER_LENGTH: usize = 3;
+/** How long, in frames, a score popup lingers. */
+const POPUP_FRAMES: u16 = 8;
+/** Score popups drift up a row every this many frames. */
+const POPUP_DRIFT_INTERVAL: u16 = 2;
+/** Frame rate of safe mode (`--safe-mode`), at most. */
+const SAFE_MODE_FRAME_RATE: u16 = 4;
+/** How long a slowdown lasts. */
+const SLOWDOWN_SECONDS: u32 = 5;
+
+/** Seconds counted down ("3, 2, 1") before the game starts, followed by a second of "GO!". */
+const COUNTDOWN_SECONDS: u32 = 3;
+/** Seconds between a wave being cleared and the next one spawning. */
+const WAVE_CLEARED_SECONDS: u32 = 1;
+/** Seconds the title screen may sit idle before the AI takes over, playing the attract-mode demo. */
+const ATTRACT_SECONDS: u32 = 15;
+
+/** Frames between two steps of an enemy shot. */
+const ENEMY_SHOT_INTERVAL: u8 = 2;
+
+/** Frames it takes a new wave to enter (drop into) formation. */
+const ENTRY_FRAMES: u16 = 8;
+
+/** Phase of the alien formation within a wave. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FormationPhase {
+    /** Aliens are still moving toward their formation rows, `frame` frames in. They neither march nor fire yet. */
+    EnteringFormation { frame: u16 },
+    /** In formation, marching and firing. */
+    Active,
+}
 
 #[derive(Debug)]
 pub struct GameState {
+    /** Size of the terminal. */
+    terminal: Size,
+    /** Where `terminal` comes from, unless `force_size` overrides it. */
+    sizes: Box<dyn viewport::SizeProvider>,
+    /** Size of the arena (see `viewport()`), what the game is actually played in. */
     size: Size,
+    args: Arguments,
+    /** ChaCha with 12 rounds, seeded by `seed` (or from entropy), its seed and position in the stream being saved along with the game (see `save`). */
+    rng: ChaCha12Rng,
+    frame: u64,
+    /** Frames actually played (countdowns, banners and pauses aside), the clock of a time attack. */
+    clock: u64,
+    score: u32,
+    lives: u8,
+    /** Score threshold the latest extra life was awarded at (see `extra_life_every`). */
+    extra_life_threshold: u32,
+    stats: stats::SessionStats,
+    /** Events emitted during the latest tick. */
+    events: Vec<events::Event>,
+    /** The recording played back in a demo (`--demo`), see `Replay`. */
+    replay: Option<replay::Playback>,
+    /** Headlines of the latest notable events, oldest first, for the HUD's ticker. At most `TICKER_LENGTH`. */
+    ticker: VecDeque<String>,
+    wave: u16,
+    phase: Phase,
+    glyphs: glyphs::GlyphMap,
+    /** Alien art pack (`alien_skin`), `None` drawing aliens with the glyph map's static sprite. */
+    sprites: Option<sprites::SpritePack>,
+    /** Times the formation marched, driving the aliens' two-frame animation. */
+    marches: u64,
     enemies: Vec<entities::Alien>,
+    /** Id of the latest alien spawned (see `Alien::id`), `0` before the first. */
+    last_alien_id: u32,
+    /** Destroyed aliens waiting to come back (`respawn_delay`), along with the frames left until they do. */
+    respawns: Vec<(u16, entities::Alien)>,
+    enemy_direction: i8,
+    /** Frames left of the formation's descent, see `DESCENT_FRAMES`. */
+    descent: u16,
+    formation: FormationPhase,
     projectiles: Vec<entities::Projectile>,
     falling_stars: Vec<entities::FallingStar>,
+    ufo: Option<entities::Ufo>,
+    popups: Vec<entities::ScorePopup>,
+    /** Debug overlay, `None` while closed. */
+    inspector: Option<inspector::Inspector>,
+    /** Best scores so far (`Arguments.leaderboard`), `None` when not kept. */
+    leaderboard: Option<leaderboard::Leaderboard>,
+    /** Initials being typed in after a game ending on a qualifying score. */
+    initials: Option<leaderboard::InitialsEntry>,
+    /** A game saved on quitting an earlier session (`Arguments.save`), offered to continue from the title screen. */
+    saved: Option<String>,
+    /** Frames the title screen has gone without any input, see `ATTRACT_SECONDS`. */
+    idle: u32,
+    /** Playing the attract-mode demo the idle title screen started, handed back as soon as somebody plays. */
+    attract: bool,
+    /** Steps the player scaled the arena by (see `Viewport::scaled`), kept across restarts. */
+    scale: i16,
+    /** Index of the active color theme in `colors::THEMES`, kept across restarts. */
+    theme: usize,
+    /** Time between presented frames, across the whole session (restarts included). */
+    frame_times: profile::FrameTimes,
+    /** Watches frame times for a terminal too slow to keep up, engaging safe mode (see `engage_safe_mode`). */
+    slow_frames: profile::SlowFrames,
+    /** Direction the next UFO flies in, alternating with every appearance. */
+    next_ufo_direction: i8,
     player: entities::Player,
+    last_shot: Option<u64>,
+    /** Frames left of an active "bullet time" slowdown, `0` when inactive. */
+    slowdown: u16,
+    /** Bonus awarded for the latest wave cleared, tallied between waves. */
+    wave_bonus: u32,
+    /** Pace and size of the current wave, see `DifficultyCurve`. */
+    wave_params: difficulty::WaveParams,
+    /** Aliens the current wave spawned with, the formation speeding up as they're shot down. */
+    wave_size: usize,
 }
 
 impl GameState {
     /** Create a new, default instance of GameState */
-    pub fn new() -> Self {
+    pub fn new(args: Arguments) -> Self {
+        let rng = match args.seed {
+            Some(seed) => ChaCha12Rng::seed_from_u64(seed),
+            None => ChaCha12Rng::from_entropy(),
+        };
+        let slow_frames = profile::SlowFrames::new(args.frame_rate.target());
+
         return GameState {
+            terminal: Size {
+                rows: 0_u16,
+                cols: 0_u16,
+            },
+            sizes: Box::new(viewport::TerminalSize),
             size: Size {
                 rows: 0_u16,
                 cols: 0_u16,
             },
+            args,
+            rng,
+            frame: 0_u64,
+            clock: 0_u64,
+            score: 0_u32,
+            lives: 0_u8,
+            extra_life_threshold: 0_u32,
+            stats: stats::SessionStats::default(),
+            events: vec!(),
+            replay: None,
+            ticker: VecDeque::with_capacity(TICKER_LENGTH),
+            wave: 0_u16,
+            phase: Phase::Playing,
+            glyphs: glyphs::GlyphMap::ascii(),
+            sprites: None,
+            marches: 0_u64,
             enemies: vec!(),
+            last_alien_id: 0_u32,
+            respawns: vec!(),
+            enemy_direction: 1_i8,
+            descent: 0_u16,
+            formation: FormationPhase::Active,
             projectiles: vec!(),
             falling_stars: vec!(),
-            player: entities::Player { pos: 0_u16 },
+            ufo: None,
+            popups: vec!(),
+            inspector: None,
+            leaderboard: None,
+            initials: None,
+            saved: None,
+            idle: 0_u32,
+            attract: false,
+            scale: 0_i16,
+            theme: 0_usize,
+            frame_times: profile::FrameTimes::new(FRAME_TIME_SAMPLES),
+            slow_frames,
+            next_ufo_direction: 1_i8,
+            player: entities::Player { pos: entities::Pos::default(), width: 1_u16 },
+            last_shot: None,
+            slowdown: 0_u16,
+            wave_bonus: 0_u32,
+            wave_params: difficulty::DifficultyCurve::default().at(1, 1),
+            wave_size: 0_usize,
         };
     }
+    /**
+     * Reset to the start of a fresh game, for a terminal of the given `size`.
+     * Reuses existing allocations, but zeroes all logical state so nothing leaks between runs.
+     * The RNG is reseeded when `args` specifies a seed, otherwise it simply carries on (advanced).
+     */
+    pub fn reset(&mut self, args: Arguments, size: Size) {
+        if let Some(seed) = args.seed {
+            self.rng = ChaCha12Rng::seed_from_u64(seed);
+        }
+
+        self.args = args;
+        self.frame = 0_u64;
+        self.clock = 0_u64;
+        self.score = 0_u32;
+        self.size = self.viewport_for(&size).size();
+        self.terminal = size;
+        self.lives = self.args.starting_lives();
+        self.extra_life_threshold = 0_u32;
+        self.stats = stats::SessionStats::default();
+        self.events.clear();
+        self.ticker.clear();
+        self.wave = 0_u16;
+        self.phase = Phase::Countdown(self.countdown_duration());
+        self.enemies.clear();
+        self.last_alien_id = 0_u32;
+        self.respawns.clear();
+        self.enemy_direction = 1_i8;
+        self.descent = 0_u16;
+        self.sprites = self.args.alien_skin.map(sprites::SpritePack::new);
+        self.marches = 0_u64;
+        self.formation = FormationPhase::Active;
+        self.projectiles.clear();
+        self.falling_stars.clear();
+        self.ufo = None;
+        self.popups.clear();
+        self.initials = None;
+        self.idle = 0_u32;
+        self.next_ufo_direction = 1_i8;
+        self.last_shot = None;
+        self.slowdown = 0_u16;
+        self.wave_bonus = 0_u32;
+
+        // Shift 'size.cols' to effectively 'half'-it, determining player's starting position.
+        self.player.width = self.glyphs.player.width() as u16;
+        self.player.pos = entities::Pos::new(self.player_row(), self.size.cols.saturating_sub(self.player.width) >> 1_u8);
+        self.seed_stars();
+        self.spawn_wave();
+    }
     /**
      * Evaluates if its current state is correct, and adjusts accordingly.
-     * Gets invoked on each iteration/loop.
+     * Gets invoked on each iteration/loop. A terminal smaller than `min_rows`x`min_cols` isn't an error,
+     * the game simply waits (see `is_playable`) until it's been resized.
      */
     pub fn evaluate_state(&mut self) -> Result<(), String> {
-        if let Some(size) = termsize::get() {
-            self.size = size;
+        let size = match self.args.force_size {
+            Some((rows, cols)) => Some(Size { rows, cols }),
+            None => self.sizes.size(),
+        };
 
-            if self.size.cols < 8_u16 || self.size.rows < 8_u16 {
-                return Err(String::from("Invalid terminal size ('termsize::get()')"));
-            }
+        if let Some(size) = size {
+            self.terminal = size;
+            self.fit_viewport();
         } else {
             return Err(String::from(
                 "Failed to compute terminal size ('termsize::get()')",
             ));
         }
 
-        // TODO! Process game state..
-
         return Ok(());
     }
-    /** Return all enemies (aliens) in the current GameState instance. */
-    pub fn enemies(self) -> Vec<entities::Alien> {
-        return self.enemies;
-    }
-    /** Return all projectiles in the current GameState instance. */
-    pub fn projectiles(self) -> Vec<entities::Projectile> {
-        return self.projectiles;
+    /** The arena for the current terminal and score. */
+    fn viewport(&self) -> viewport::Viewport {
+        return self.viewport_for(&self.terminal);
     }
-    /** Return all projectiles in the current GameState instance. */
-    pub fn player(self) -> entities::Player {
-        return self.player;
+    /**
+     * The arena for a terminal of the given size, as scaled by the player,
+     * leaving room for the inspector's panel while it's open.
+     */
+    fn viewport_for(&self, terminal: &Size) -> viewport::Viewport {
+        let viewport = match self.args.arena_growth.filter(|every| *every > 0) {
+            Some(every) => viewport::Viewport::grown(terminal, self.args.min_rows, self.args.min_cols, self.score / every),
+            None => viewport::Viewport::full(terminal),
+        }
+        .scaled(terminal, self.scale, self.args.min_rows, self.args.min_cols);
+
+        if self.inspector.is_some() {
+            return viewport.reserve_cols(terminal, inspector::PANEL_WIDTH, self.args.min_cols);
+        }
+
+        return viewport;
     }
-}
+    /**
+     * Resize the arena to the current `viewport()`, should it have changed (terminal resized, or grown with score).
+     * Everything is pulled back in bounds, projectiles left outside are dropped and the starfield is reseeded to match.
+     */
+    fn fit_viewport(&mut self) {
+        let viewport = self.viewport();
+        if viewport.matches(&self.size) {
+            return;
+        }
 
-pub struct Arguments {
-    pub frame_rate: Option<u8>,
-    pub bullet_time: u8,
-    pub enemy_time: u8,
-    pub panic_on_errors: bool,
-}
+        self.size = viewport.size();
+        self.player.pos.row = self.player_row();
+        self.player.clamp(self.size.cols);
 
-/**
- * Starts the game by initiating the loop.
- */
-pub fn start(args: Arguments) {
-    let target_frame_time: f32 = match args.frame_rate {
-        Some(rate) if rate > 0 => 1_f32 / rate as f32,
-        _ => 1_f32 / u8::MAX as f32, // "Uncapped".
-    };
+        let cols = self.size.cols;
+        for alien in self.enemies.iter_mut() {
+            alien.pos.col = alien.bounds().clamped_col(cols);
+        }
+        if let Some(ufo) = self.ufo.as_mut() {
+            ufo.pos.col = ufo.bounds().clamped_col(cols);
+        }
 
-    let mut state = GameState::new();
-    state.evaluate_state().expect(
-        "Failed to start! 'GameState::evaluate_state()' paniced! Are you running TempleOS?",
-    );
-
-    // Populate state..
-    let mut rng = rand::thread_rng();
-    while state.falling_stars.len() < 4 { // Testing 4 stars!
-        let col: u16 = rng.gen_range(1..state.size.cols);
-        for star in state.falling_stars.iter() {
-            if star.col == col {
-                continue;
+        let (top, bottom) = (self.play_top(), self.play_bottom());
+        let size = Size { rows: bottom, cols };
+        self.projectiles.retain(|projectile| projectile.pos.in_bounds(&size) && projectile.pos.row >= top);
+        for popup in self.popups.iter_mut() {
+            popup.pos = popup.pos.clamped(&size);
+        }
+        self.seed_stars();
+    }
+    /**
+     * Advance the simulation by a single frame, applying the given actions first.
+     * Does not touch the terminal, so it can be driven headless (by the AI, for example).
+     *
+     * Every tick runs the same stages in the same, fixed, order. The order is part of the rules,
+     * as it decides the outcome of things happening "simultaneously":
+     *  1. Input       - actions are applied to the player (movement, firing).
+     *  2. Projectiles - player shots travel `projectile_speed` rows, enemy shots `enemy_projectile_speed` (the starfield and score popups drift here too).
+     *  3. Enemies     - the formation enters, or marches a column / drops a row. The UFO flies `ufo_speed` columns (or appears).
+     *  4. Enemy fire  - a front-row alien may fire.
+     *  5. Collisions  - swept projectile hits are resolved, removing destroyed aliens.
+     *  6. Status      - the arena grows with score, then wave cleared, invasion and game over are evaluated.
+     *
+     * Since collisions resolve before the status is evaluated, an alien destroyed on the same frame it reaches
+     * the invasion row (see `invasion_row`) is gone by then and doesn't count as an invasion, while an enemy shot hitting the player
+     * ends the game even if the last alien was shot down that very frame.
+     *
+     * The stages only all run while `Playing`. During a countdown or the interlude after a cleared wave only
+     * the ship may move, and in every other phase nothing does (bar the starfield behind the pause menu,
+     * see `Arguments.stars_animate_while_paused`). Pausing takes effect before the stages would run.
+     * Once done, `advance_phase` decides which phase the next tick is in.
+     */
+    pub fn tick(&mut self, actions: &[Action]) {
+        self.step_inspector(actions);
+        self.step_scale(actions);
+        self.step_theme(actions);
+        self.events.clear();
+
+        match self.phase {
+            Phase::Playing if !actions.contains(&Action::Pause) => {
+                self.frame += 1;
+                self.clock += 1;
+                self.step_input(actions);
+                self.step_projectiles();
+                self.step_enemies();
+                self.step_enemy_fire();
+                self.resolve_collisions();
+                self.step_status();
+                self.slowdown = self.slowdown.saturating_sub(1);
             }
+            Phase::Countdown(_) | Phase::WaveCleared(_) => {
+                self.frame += 1;
+                let movement: Vec<Action> = actions.iter().copied().filter(|action| *action != Action::Fire).collect();
+                self.step_input(&movement);
+            }
+            Phase::Paused(_) if self.args.stars => self.advance_stars(true),
+            Phase::GameOver | Phase::Won => self.step_initials(actions),
+            Phase::Title if actions.is_empty() => self.idle += 1,
+            Phase::Title => self.idle = 0_u32,
+            _ => {}
         }
 
-        state.falling_stars.push(entities::FallingStar { pos: 0, col });
+        self.advance_phase(actions);
+        self.stats.frames = self.frame;
+    }
+    /** Open / close the inspector and move its cursor, whatever the game is up to (paused or not). */
+    fn step_inspector(&mut self, actions: &[Action]) {
+        for action in actions {
+            if *action == Action::ToggleInspector {
+                self.inspector = match self.inspector {
+                    Some(_) => None,
+                    None => Some(inspector::Inspector::default()),
+                };
+            } else if let Some(inspector) = self.inspector.as_mut() {
+                inspector.handle(*action, &self.size);
+            }
+        }
     }
+    /**
+     * Move on to the next phase, the one place transitions between phases happen. Runs at the end of every tick,
+     * reacting to its actions (starting, pausing, the pause menu), its events (wave cleared, game over) and timers.
+     *
+     *  Title -> Countdown -> Playing <-> Paused
+     *  Title -> (saved game) Paused
+     *  Title -> (idle) Countdown, the AI playing the attract-mode demo until it's over
+     *                        Playing <-> WaveCleared
+     *                        Playing  -> GameOver | Won
+     *
+     * `Restart` from the pause menu starts over from the countdown, `Quit` (from the menu) ends the game.
+     */
+    fn advance_phase(&mut self, actions: &[Action]) {
+        let emitted = |event: &events::Event| self.events.contains(event);
+        let cleared = self.events.iter().any(|event| matches!(event, events::Event::WaveCleared { .. }));
+        let previous = self.phase;
+
+        self.phase = match self.phase {
+            Phase::Title if actions.iter().any(|action| matches!(action, Action::Fire | Action::Confirm)) => {
+                Phase::Countdown(self.countdown_duration())
+            }
+            Phase::Title if actions.contains(&Action::Continue) && self.saved.is_some() => self.continue_saved(),
+            Phase::Title if self.idle >= self.attract_delay() => self.start_attract(),
+            Phase::Countdown(frames) if frames > 1 => Phase::Countdown(frames - 1),
+            Phase::Countdown(_) => Phase::Playing,
+            Phase::Playing if emitted(&events::Event::GameOver) => Phase::GameOver,
+            Phase::Playing if emitted(&events::Event::CampaignWon) => Phase::Won,
+            Phase::Playing if cleared => Phase::WaveCleared(self.interlude_duration()),
+            Phase::Playing if actions.contains(&Action::Pause) => Phase::Paused(menu::PauseMenu::default()),
+            Phase::WaveCleared(frames) if frames > 1 => Phase::WaveCleared(frames - 1),
+            Phase::WaveCleared(_) => {
+                self.spawn_wave();
+                Phase::Playing
+            }
+            Phase::Paused(menu) => self.advance_pause_menu(menu, actions),
+            phase => phase,
+        };
 
-    // Shift 'size.rows' to effectively 'half'-it, determining player's starting position.
-    state.player.pos = state.size.rows >> 1_u8;
+        // The attract-mode demo ending, the title screen waits on a fresh game again.
+        if self.attract && matches!(self.phase, Phase::GameOver | Phase::Won) {
+            self.end_attract();
+        }
 
-    let mut t = time::Instant::now();
-    let mut frame_time = 0_f32;
-    loop {
-        if frame_time >= target_frame_time {
-            let meassure: u16 = (1_f32 / frame_time).round() as u16;
-            t = time::Instant::now();
-            frame_time = 0_f32;
+        // A game ending on a qualifying score makes the leaderboard, once the player typed in their initials.
+        if matches!(self.phase, Phase::GameOver | Phase::Won) && !matches!(previous, Phase::GameOver | Phase::Won) {
+            let qualifies = self.leaderboard.as_ref().is_some_and(|leaderboard| leaderboard.qualifies(self.score));
+            self.initials = qualifies.then(leaderboard::InitialsEntry::default);
+        }
+    }
+    /**
+     * Pick the saved game up where it was left off. A game saved mid-play waits on the pause menu until resumed.
+     * Returns the phase to carry on in, the title screen should the save not be continued after all.
+     */
+    fn continue_saved(&mut self) -> Phase {
+        let Some(text) = self.saved.take() else {
+            return Phase::Title;
+        };
+        if save::restore(self, &text).is_err() {
+            return Phase::Title;
+        }
 
-            // Run an iteration of the game loop.
-            let frame_execution_result = game_loop(&mut state);
-            match frame_execution_result {
-                Ok(_) => render(meassure, &state),
-                Err(error_message) => {
-                    if args.panic_on_errors {
-                        panic!("Panic! {}", error_message);
-                    } else {
-                        println!("Error! {}", error_message);
-                    }
+        return match self.phase {
+            Phase::Playing => Phase::Paused(menu::PauseMenu::default()),
+            phase => phase,
+        };
+    }
+    /** Hand the idle title screen over to the AI (`ai::decide`), playing the game as a demo. */
+    fn start_attract(&mut self) -> Phase {
+        self.attract = true;
+        self.args.demo = true;
+        return Phase::Countdown(self.countdown_duration());
+    }
+    /** Back to the title screen from the attract-mode demo, with a fresh game waiting to be started. */
+    fn end_attract(&mut self) {
+        self.attract = false;
+        self.args.demo = false;
+        self.restart();
+        self.phase = Phase::Title;
+    }
+    /** Navigate the pause menu, acting on the first entry picked. Returns the phase to carry on in. */
+    fn advance_pause_menu(&mut self, mut menu: menu::PauseMenu, actions: &[Action]) -> Phase {
+        for action in actions {
+            match menu.handle(*action) {
+                Some(menu::PauseOption::Resume) => return Phase::Playing,
+                Some(menu::PauseOption::Restart) => {
+                    self.restart();
+                    return self.phase;
                 }
+                Some(menu::PauseOption::Quit) => return Phase::Quit,
+                None => {}
             }
+        }
+
+        return Phase::Paused(menu);
+    }
+    /**
+     * Scale the arena up / down, whatever the game is up to, pulling everything back in bounds.
+     * Steps that wouldn't change anything (the arena being as large / small as it gets) are ignored.
+     */
+    fn step_scale(&mut self, actions: &[Action]) {
+        let before = self.scale;
+        for action in actions {
+            let steps = match action {
+                Action::ScaleUp => 1_i16,
+                Action::ScaleDown => -1_i16,
+                _ => continue,
+            };
 
-            continue;
+            let viewport = self.viewport();
+            self.scale = self.scale.saturating_add(steps);
+            if self.viewport() == viewport {
+                self.scale -= steps;
+            }
         }
 
-        // Increment 'meassure' by elapsed time (..meassured in microseconds) in seconds.
-        frame_time += t.elapsed().subsec_micros() as f32 / 1000000_f32;
+        if self.scale != before {
+            self.fit_viewport();
+        }
     }
-}
+    /** Cycle through the color themes, whatever the game is up to. */
+    fn step_theme(&mut self, actions: &[Action]) {
+        let presses = actions.iter().filter(|action| **action == Action::CycleTheme).count();
+        self.theme = (self.theme + presses) % colors::THEMES.len();
+    }
+    /** The active color theme. */
+    fn theme(&self) -> &colors::Theme {
+        return &colors::THEMES[self.theme % colors::THEMES.len()];
+    }
+    /** Type in the player's initials after a qualifying score, placing it on the leaderboard once confirmed. */
+    fn step_initials(&mut self, actions: &[Action]) {
+        let (Some(entry), Some(leaderboard)) = (self.initials.as_mut(), self.leaderboard.as_mut()) else {
+            return;
+        };
 
-/**
- * Main game loop.
- * Runs capped to the specified framerate, with the actual framerate meassurement passed as an argument.
- */
-fn game_loop(state: &mut GameState) -> Result<(), String> {
-    // Clear the previous screen.
-    if let Err(e) = clearscreen::clear() {
-        return Err(format!(
-            "Cought an error calling 'clearscreen::clear()', {e}"
-        ));
+        let Some(initials) = actions.iter().find_map(|action| entry.handle(*action)) else {
+            return;
+        };
+
+        let place = leaderboard.insert(leaderboard::Entry { score: self.score, initials, date: leaderboard::today() });
+        self.initials = None;
+        if let Some(place) = place {
+            self.emit(events::Event::HighScore { place });
+        }
+    }
+    /** Start over with a fresh game, keeping the current arguments and terminal size. */
+    fn restart(&mut self) {
+        let size = Size { rows: self.terminal.rows, cols: self.terminal.cols };
+        self.reset(self.args.clone(), size);
+    }
+    /** Stage 1 of `tick`, apply the player's actions. */
+    fn step_input(&mut self, actions: &[Action]) {
+        for action in actions {
+            self.apply_action(*action);
+        }
     }
+    /**
+     * Stage 2 of `tick`, move projectiles (and stars).
+     * Player projectiles keep their pace during a slowdown, everything else is scaled by 'cadence'.
+     */
+    fn step_projectiles(&mut self) {
+        if self.args.stars && self.frame.is_multiple_of(self.cadence(1)) {
+            self.advance_stars(false);
+        }
+        self.advance_popups();
 
-    // Evaluate / Re-calculate game-state.
-    // This validates enemy, player and projectile position in relation to current terminal size.
-    state.evaluate_state()?;
+        self.advance_projectiles(entities::Owner::Player, self.args.projectile_speed.max(1) as u16);
+        if self.frame.is_multiple_of(self.cadence(ENEMY_SHOT_INTERVAL)) {
+            self.advance_projectiles(entities::Owner::Enemy, self.args.enemy_projectile_speed.max(1) as u16);
+        }
+    }
+    /** Stage 3 of `tick`, move the alien formation and the UFO. */
+    fn step_enemies(&mut self) {
+        if let FormationPhase::EnteringFormation { frame } = self.formation {
+            self.advance_formation_entry(frame + 1);
+        } else if self.descent > 0 {
+            self.advance_descent();
+        } else if self.frame.is_multiple_of(self.cadence(self.enemy_step_interval())) {
+            self.advance_enemies();
+        }
 
-    return Ok(());
-}
+        if let Some(ufo) = self.ufo.as_mut() {
+            ufo.from_col = ufo.pos.col;
+        }
+        if self.frame.is_multiple_of(self.cadence(1)) {
+            self.advance_ufo();
+        }
+        if self.ufo.is_none() && self.args.ufo_interval > 0 && self.frame.is_multiple_of(self.args.ufo_interval as u64) {
+            self.roll_ufo();
+        }
+    }
+    /**
+     * Stage 4 of `tick`, let the aliens fire (not while entering formation).
+     * Scheduled purely off the frame counter (and seeded RNG), so headless runs are reproducible.
+     */
+    fn step_enemy_fire(&mut self) {
+        if self.formation == FormationPhase::Active && self.frame.is_multiple_of(self.enemy_fire_interval()) {
+            self.enemy_fire();
+        }
+    }
+    /** Stage 6 of `tick`, evaluate whether the wave was cleared, or the game lost (and grow the arena with score). */
+    fn step_status(&mut self) {
+        if self.events.contains(&events::Event::GameOver) {
+            return; // Already decided during collisions (player hit).
+        }
+        if self.time_left() == Some(0) {
+            // Time's up, however many lives are left.
+            self.emit(events::Event::GameOver);
+            return;
+        }
 
-fn render(frame_rate: u16, state: &GameState) {
-    println!();
-    let mut line: String;
-    // Line #1 - Debugging / Messaging
-    let mut message = right_pad(format!("Framerate: {frame_rate}"), state.size.rows.into());
-    println!("{}", message);
+        self.fit_viewport();
+        self.step_respawns();
 
-    let start_at_row: u16 = if state.size.rows > 10 {
-        println!("{}", "=".repeat(state.size.rows as usize));
-        2
-    } else {
-        1
-    };
+        // Aliens yet to respawn keep the wave going.
+        if self.enemies.is_empty() && self.respawns.is_empty() {
+            let bonus = self.args.scoring.wave_bonus(self.lives);
+            self.award(bonus);
+            self.wave_bonus = bonus;
+            self.emit(events::Event::WaveCleared { wave: self.wave, bonus });
+            if self.args.total_waves.is_some_and(|total| self.wave >= total) {
+                self.emit(events::Event::CampaignWon);
+            }
+        } else if self.args.invincible {
+            // Aliens pass harmlessly through an invincible ship, vanishing once they leave the play area.
+            let bottom = self.play_bottom();
+            self.enemies.retain(|alien| alien.pos.row < bottom);
+        } else if self.enemies.iter().any(|alien| alien.pos.row >= self.invasion_row()) {
+            if self.args.practice {
+                // Practice goes on forever, an invasion only restarts the wave (from scratch, in formation).
+                self.enemies.clear();
+                self.descent = 0_u16;
+                self.wave -= 1;
+                self.spawn_wave();
+            } else if self.args.invasion_costs_life {
+                // The invaders crash into the ship, costing a life (and themselves) rather than the game.
+                let row = self.invasion_row();
+                self.enemies.retain(|alien| alien.pos.row < row);
+                self.player_hit();
+            } else {
+                self.emit(events::Event::GameOver);
+            }
+        }
+    }
+    /** Count down the aliens waiting to respawn, bringing back those whose time has come where they were destroyed. */
+    fn step_respawns(&mut self) {
+        let mut index = 0_usize;
+        while index < self.respawns.len() {
+            let (frames_left, _) = &mut self.respawns[index];
+            if *frames_left > 0 {
+                *frames_left -= 1;
+                index += 1;
+                continue;
+            }
 
-    let mut current_row = start_at_row;
-    while current_row < state.size.rows {
-        let mut start_at_col: Option<u16> = None; // Determine what the first 'col' is for this row
-        let mut star_indicies: Vec<u8> = vec![]; // Effectively 'filters' falling-stars
+            let (_, mut alien) = self.respawns.remove(index);
+            self.last_alien_id += 1;
+            alien.id = self.last_alien_id;
+            alien.pos.col = alien.bounds().clamped_col(self.size.cols);
+            self.enemies.push(alien);
+        }
+    }
+    /** Publish an event on the bus, for this tick. */
+    fn emit(&mut self, event: events::Event) {
+        self.stats.record(&event);
+        if let Some(headline) = event.headline() {
+            if self.ticker.len() == TICKER_LENGTH {
+                self.ticker.pop_front(); // Scrolled out by the newer one.
+            }
+            self.ticker.push_back(headline);
+        }
+        self.events.push(event);
+    }
+    /** Apply a single action to the player. */
+    fn apply_action(&mut self, action: Action) {
+        match action {
+            Action::MoveLeft => self.player.move_left(self.size.cols, self.args.movement_mode),
+            Action::MoveRight => self.player.move_right(self.size.cols, self.args.movement_mode),
+            Action::MoveTo(col) => {
+                self.player.pos.col = col.saturating_sub(self.player.width >> 1);
+                self.player.clamp(self.size.cols);
+            }
+            Action::Fire => {
+                let cooldown = self.args.bullet_time as u64;
+                if self.last_shot.is_some_and(|frame| self.frame - frame < cooldown) {
+                    return;
+                }
+
+                self.last_shot = Some(self.frame);
+                self.emit(events::Event::ShotFired);
+                let row = self.player_row().saturating_sub(1);
+                self.projectiles.push(entities::Projectile {
+                    pos: entities::Pos::new(row, self.muzzle_col()),
+                    from_row: row,
+                    owner: entities::Owner::Player,
+                    drift: 0_i8,
+                    y: row as f32,
+                    velocity: -(self.args.projectile_speed.max(1) as f32),
+                });
+            }
+            Action::Pause
+            | Action::MenuUp
+            | Action::MenuDown
+            | Action::Confirm
+            | Action::ToggleInspector
+            | Action::ScaleUp
+            | Action::ScaleDown
+            | Action::CycleTheme
+            | Action::Continue
+            | Action::Type(_)
+            | Action::Erase
+            | Action::CursorUp
+            | Action::CursorDown
+            | Action::CursorLeft
+            | Action::CursorRight
+            | Action::Quit => {}
+        }
+    }
+    /** Stars to scatter across the current terminal, `star_density` per thousand cells (at most one per column). */
+    fn star_count(&self) -> usize {
+        if !self.args.stars {
+            return 0;
+        }
+
+        let cells = self.size.rows as f32 * self.size.cols as f32;
+        let count = (cells * self.args.star_density.max(0_f32) / 1000_f32).round() as usize;
+        return count.min(self.size.cols as usize);
+    }
+    /** Replace the starfield with `star_count()` stars, scattered across the play area in distinct columns. */
+    fn seed_stars(&mut self) {
+        self.falling_stars.clear();
+
+        let top = self.play_top();
+        let bottom = self.play_bottom();
+        if bottom <= top {
+            return;
+        }
 
-        for star in &state.falling_stars {
-            *star_indicies.last_mut().unwrap() += 1;
+        let count = self.star_count();
+        for col in rand::seq::index::sample(&mut self.rng, self.size.cols as usize, count) {
+            let row = self.rng.gen_range(top..bottom);
+            self.falling_stars.push(entities::FallingStar { pos: entities::Pos::new(row, col as u16), entity: self.glyphs.star });
+        }
+    }
+    /** Drift the stars down a row, wrapping them back to the top. Frozen while `paused`, unless asked otherwise. */
+    fn advance_stars(&mut self, paused: bool) {
+        if paused && !self.args.stars_animate_while_paused {
+            return;
+        }
+
+        let top = self.play_top();
+        let bottom = self.play_bottom();
+        for star in self.falling_stars.iter_mut() {
+            star.pos.row += 1;
+            if star.pos.row >= bottom {
+                star.pos.row = top;
+                star.pos.col = self.rng.gen_range(0..self.size.cols);
+            }
+        }
+    }
+    /** Age every score popup a frame, drifting them up (never above the play area) and dropping expired ones. */
+    fn advance_popups(&mut self) {
+        let top = self.play_top();
+        self.popups.retain_mut(|popup| {
+            popup.frames_left = popup.frames_left.saturating_sub(1);
+            if popup.frames_left.is_multiple_of(POPUP_DRIFT_INTERVAL) && popup.pos.row > top {
+                popup.pos.row -= 1;
+            }
+
+            return popup.frames_left > 0;
+        });
+    }
+    /** Leave a score popup for `points` centered on the given cell. */
+    fn popup(&mut self, points: u32, row: u16, col: u16) {
+        self.popups.push(entities::ScorePopup { value: points, pos: entities::Pos::new(row, col), frames_left: POPUP_FRAMES });
+    }
+    /** Fly the UFO `ufo_speed` columns onward (never past the edge), removing it once it reaches the far edge. */
+    fn advance_ufo(&mut self) {
+        let cols = self.size.cols;
+        let speed = self.args.ufo_speed.max(1) as u16;
+        let Some(ufo) = self.ufo.as_mut() else {
+            return;
+        };
+
+        let leaving = match ufo.direction {
+            1 => ufo.pos.col + ufo.width >= cols,
+            _ => ufo.pos.col == 0,
+        };
+
+        if leaving {
+            self.ufo = None;
+        } else if ufo.direction == 1 {
+            ufo.pos.col = (ufo.pos.col + speed).min(cols.saturating_sub(ufo.width));
+        } else {
+            ufo.pos.col = ufo.pos.col.saturating_sub(speed);
+        }
+    }
+    /**
+     * Give the UFO its `ufo_chance` to appear (decided by the seeded RNG), entering from the left or right edge,
+     * alternating between appearances.
+     */
+    fn roll_ufo(&mut self) {
+        if !self.rng.gen_bool(self.args.ufo_chance.clamp(0_f32, 1_f32) as f64) {
+            return;
+        }
 
-            if star.pos != current_row {
-                continue; // The star is not on the current row..
+        let width = self.glyphs.ufo.width() as u16;
+        let direction = self.next_ufo_direction;
+        self.next_ufo_direction = -direction;
+        let col = if direction == 1 { 0 } else { self.size.cols.saturating_sub(width) };
+        self.ufo = Some(entities::Ufo {
+            pos: entities::Pos::new(self.play_top(), col),
+            from_col: col,
+            width,
+            direction,
+        });
+    }
+    /**
+     * Move every projectile fired by `owner` `speed` rows, up for the player and down for enemies,
+     * discarding those that leave the play area. The row each started from is kept (`from_row`),
+     * so collisions can be checked along the whole path rather than just where it ended up.
+     * Under `gravity`, player projectiles arc instead, slowing down on their way up until they fall back down.
+     */
+    fn advance_projectiles(&mut self, owner: entities::Owner, speed: u16) {
+        let top = self.play_top();
+        let bottom = self.play_bottom();
+        let cols = self.size.cols.max(1);
+        let pattern = self.args.enemy_bullet_pattern;
+        let gravity = self.args.gravity;
+        let mut missed = 0;
+        self.projectiles.retain_mut(|projectile| {
+            if projectile.owner != owner {
+                return true;
             }
 
-            if start_at_col.is_none() || star.col - 1 < start_at_col.unwrap() {
-                start_at_col = Some(star.col - 1);
+            projectile.from_row = projectile.pos.row;
+            match owner {
+                entities::Owner::Player if projectile.pos.row <= top => {
+                    missed += 1;
+                    return false;
+                }
+                entities::Owner::Player if gravity > 0_f32 => {
+                    projectile.velocity += gravity;
+                    projectile.y += projectile.velocity;
+                    if projectile.y.round() >= bottom as f32 {
+                        missed += 1;
+                        return false;
+                    }
+
+                    // Collisions go by the rounded row.
+                    projectile.pos.row = projectile.y.round().max(top as f32) as u16;
+                }
+                entities::Owner::Player => projectile.pos.row = projectile.pos.row.saturating_sub(speed).max(top),
+                entities::Owner::Enemy if projectile.pos.row + 1 >= bottom => return false,
+                entities::Owner::Enemy => {
+                    projectile.pos.row = (projectile.pos.row + speed).min(bottom - 1);
+                    if pattern == BulletPattern::ZigZag {
+                        // Sidestep every step, alternating sides (bouncing off the edges).
+                        let col = projectile.pos.col as i32 + projectile.drift as i32;
+                        if col < 0 || col >= cols as i32 {
+                            projectile.drift = -projectile.drift;
+                        }
+                        projectile.pos.col = (projectile.pos.col as i32 + projectile.drift as i32).clamp(0, cols as i32 - 1) as u16;
+                        projectile.drift = -projectile.drift;
+                    }
+                }
             }
 
-            star_indicies.push(*star_indicies.last().unwrap());
+            return true;
+        });
+
+        for _ in 0..missed {
+            self.emit(events::Event::ShotMissed);
         }
+    }
+    /**
+     * March the alien formation one column in its current direction.
+     * When the formation would leave the screen it turns around and starts descending a row instead (see `advance_descent`).
+     */
+    fn advance_enemies(&mut self) {
+        let cols = self.size.cols;
+        let direction = self.enemy_direction;
+        let bounce = self.enemies.iter().any(|alien| {
+            (direction < 0 && alien.pos.col == 0) || (direction > 0 && alien.pos.col + alien.width >= cols)
+        });
 
-        message = "=".repeat(state.size.rows as usize);
-        if start_at_col.is_none() {
-            println!("{}", message);
-            current_row += 1;
-            continue;
+        if bounce {
+            self.enemy_direction = -direction;
+            self.descent = DESCENT_FRAMES;
+            return;
         }
 
-        for index in star_indicies {
-            replace_at(&mut message, state.falling_stars[index as usize].entity, state.falling_stars[index as usize].col)
+        for alien in self.enemies.iter_mut() {
+            alien.pos.col = alien.pos.col.saturating_add_signed(direction as i16);
         }
+        self.marches += 1;
     }
-}
+    /**
+     * Move the formation a `DESCENT_FRAMES`th of a row down, so a bounce animates over a few frames
+     * rather than teleporting. Aliens settle exactly on the row below once the descent is over.
+     */
+    fn advance_descent(&mut self) {
+        self.descent -= 1;
+        let settled = self.descent == 0;
+        for alien in self.enemies.iter_mut() {
+            alien.y += 1_f32 / DESCENT_FRAMES as f32;
+            if settled {
+                alien.y = alien.y.round();
+            }
 
-fn replace_at(content: &mut String, character: char, index: u16) -> () {
-    content.replace_range(
-        content
-            .char_indices()
-            .nth(index.into())
-            .map(|(i, c)| (i..i + c.len_utf8()))
-            .unwrap(),
-        &character.to_string()
-    )
-}
+            alien.pos.row = alien.y.round() as u16;
+        }
+    }
+    /**
+     * Frames between two marches of the formation, shrinking with the aliens left (the classic speed-up),
+     * yet never below `min_enemy_time` lest a lone alien march every frame.
+     */
+    fn enemy_step_interval(&self) -> u8 {
+        let full = self.wave_params.march_interval.max(1) as usize;
+        let paced = (full * self.enemies.len()).div_ceil(self.wave_size.max(1));
+        return paced.clamp(self.args.min_enemy_time.clamp(1, full as u8) as usize, full) as u8;
+    }
+    /**
+     * Frames between two enemy fire attempts, as set for the wave by the `DifficultyCurve`, scaled by any active slowdown.
+     * Kept separate from the marching cadence so the two can diverge.
+     */
+    fn enemy_fire_interval(&self) -> u64 {
+        return self.cadence(self.wave_params.fire_interval);
+    }
+    /**
+     * Aliens allowed to fire under `fire_rule`. Under `FireRule::FrontRow` that's the lowest living alien
+     * of each column, those without another alien below them in any column their sprite covers.
+     */
+    fn eligible_shooters(&self) -> Vec<usize> {
+        return (0..self.enemies.len())
+            .filter(|index| {
+                let alien = &self.enemies[*index];
+                self.args.fire_rule == FireRule::Any
+                    || !self.enemies.iter().any(|other| other.pos.row > alien.pos.row && other.bounds().shares_cols(&alien.bounds()))
+            })
+            .collect();
+    }
+    /**
+     * Pick one of the eligible aliens (see `eligible_shooters`) to fire a shot down at the player.
+     * Selection is weighted toward aliens in or near the player's column, by a factor of `1 + fire_bias`
+     * for every column closer they are. A `fire_bias` of zero picks uniformly at random.
+     * Holds fire while `max_enemy_projectiles` enemy shots are already in flight.
+     */
+    fn enemy_fire(&mut self) {
+        if let Some(max) = self.args.max_enemy_projectiles {
+            let in_flight = self.projectiles.iter().filter(|projectile| projectile.owner == entities::Owner::Enemy).count();
+            if in_flight >= max as usize {
+                return;
+            }
+        }
 
-/**
- * Pad-out the string length to fill out the remaining cells of a row.
- */
-fn right_pad(mut string_content: String, length: usize) -> String {
-    let char_count: usize = string_content.chars().count();
+        let shooters = self.eligible_shooters();
+        if shooters.is_empty() {
+            return;
+        }
 
-    if char_count == length {
-        return string_content;
-    } else if char_count > length {
-        return string_content.split_at(length).0.into();
-    };
+        let target = self.player.center();
+        let base = 1_f32 + self.args.fire_bias.max(0_f32);
+        let weights: Vec<f32> = shooters
+            .iter()
+            .map(|index| {
+                let alien = &self.enemies[*index];
+                let distance = if alien.occupies(alien.pos.row, target) {
+                    0_u16
+                } else {
+                    alien.center().abs_diff(target).div_ceil(alien.width.max(1))
+                };
 
-    string_content += &" ".repeat(length - char_count);
-    return string_content;
-}
+                base.powi(-(distance as i32)).max(f32::MIN_POSITIVE)
+            })
+            .collect();
 
-/**
- * ..I don't need no NPM Package!
- */
-fn left_pad(start_index: u16, mut string_content: String, length: u16) -> String {
-    if start_index >= length {
-        return vec![" "; length as usize].join("");
-    };
+        let shooter = match WeightedIndex::new(&weights) {
+            Ok(distribution) => &self.enemies[shooters[distribution.sample(&mut self.rng)]],
+            Err(_) => return,
+        };
 
-    let line: String = match start_index {
-        0 => String::new(),
-        // _ => vec![" "; start_index as usize].join(""),
-        _ => " ".repeat(start_index.into())
-    };
+        let projectile = entities::Projectile {
+            pos: entities::Pos::new(shooter.pos.row + 1, shooter.center()),
+            from_row: shooter.pos.row + 1,
+            owner: entities::Owner::Enemy,
+            drift: match self.args.enemy_bullet_pattern {
+                BulletPattern::Straight => 0_i8,
+                BulletPattern::ZigZag if self.rng.gen_bool(0.5) => 1_i8,
+                BulletPattern::ZigZag => -1_i8,
+            },
+            y: (shooter.pos.row + 1) as f32,
+            velocity: 0_f32,
+        };
+        self.projectiles.push(projectile);
+    }
+    /**
+     * Remove any player projectile and alien sharing a cell, awarding score for each kill.
+     * A projectile hitting any cell of an alien's sprite costs it a hit point, destroying the whole alien
+     * at zero (only the destroying hit scores).
+     * Enemy projectiles reaching any cell of the player's ship cost a life.
+     *
+     * Collisions are swept, every cell a projectile passed through during its latest step is tested
+     * in the order it travelled them, so fast projectiles can't tunnel through (or past) anything.
+     * The first alien (or UFO) along the path is the one that's hit.
+     */
+    fn resolve_collisions(&mut self) {
+        let mut index = 0;
+        while index < self.projectiles.len() {
+            let projectile = &self.projectiles[index];
+            let path = projectile.path();
+
+            if projectile.owner == entities::Owner::Enemy {
+                if projectile.swept().intersects(&self.player.bounds()) {
+                    self.projectiles.remove(index);
+                    self.player_hit();
+                    continue;
+                }
+
+                index += 1;
+                continue;
+            }
+
+            // The UFO's path this step is checked rather than where it ended up, as it may fly several columns a step.
+            let col = projectile.pos.col;
+            let ufo_row = path.iter().copied().find(|row| self.ufo.as_ref().is_some_and(|ufo| ufo.swept().contains(*row, col)));
+            let hit = path.into_iter().take_while(|row| Some(*row) != ufo_row).find_map(|row| {
+                self.enemies.iter().position(|alien| alien.occupies(row, col))
+            });
+
+            if hit.is_none() && ufo_row.is_some() {
+                self.projectiles.remove(index);
+                if let Some(ufo) = self.ufo.take() {
+                    let points = self.args.scoring.ufo_points;
+                    self.award(points);
+                    self.popup(points, ufo.pos.row, ufo.pos.col + (ufo.width >> 1));
+                    self.emit(events::Event::UfoDestroyed { row: ufo.pos.row, col: ufo.pos.col, points });
+                }
+                continue;
+            }
+
+            if let Some(alien_index) = hit {
+                self.projectiles.remove(index);
 
-    string_content.insert_str(0, &line); 
-    return string_content;
+                let alien = &mut self.enemies[alien_index];
+                alien.hp = alien.hp.saturating_sub(1);
+                if alien.hp > 0 {
+                    let (id, row, col) = (alien.id, alien.pos.row, alien.pos.col);
+                    self.emit(events::Event::AlienCracked { id, row, col });
+                    continue;
+                }
+
+                let alien = self.enemies.remove(alien_index);
+                if let Some(delay) = self.args.respawn_delay {
+                    // Back without a shield, should it have had one.
+                    self.respawns.push((delay, entities::Alien { hp: 1, ..alien.clone() }));
+                }
+                let points = self.args.scoring.alien_points;
+                self.award(points);
+                self.popup(points, alien.pos.row, alien.center());
+                self.emit(events::Event::AlienDestroyed { id: alien.id, row: alien.pos.row, col: alien.pos.col, points });
+                if alien.tier > 1 {
+                    self.split(&alien);
+                }
+                continue;
+            }
+
+            index += 1;
+        }
+    }
+    /** Replace a destroyed (splitting) alien with two fragments a tier lower, side by side where it stood. */
+    fn split(&mut self, alien: &entities::Alien) {
+        let left = alien.center().saturating_sub(1).min(self.size.cols.saturating_sub(2));
+        for col in [left, left + 1] {
+            self.last_alien_id += 1;
+            self.enemies.push(entities::Alien {
+                id: self.last_alien_id,
+                pos: entities::Pos::new(alien.pos.row, col),
+                y: alien.y,
+                width: 1,
+                target_row: alien.target_row,
+                rank: alien.rank,
+                hp: 1,
+                tier: alien.tier - 1,
+                fragment: true,
+            });
+        }
+    }
+    /**
+     * The player's ship was hit, costing a life. Losing the last one ends the game (see `advance_phase`).
+     * Invincible ships shrug it off, as do ships already out of lives.
+     */
+    fn player_hit(&mut self) {
+        if self.args.invincible || self.lives == 0 {
+            return;
+        }
+
+        self.emit(events::Event::PlayerHit);
+        if self.args.practice {
+            return; // Unlimited lives.
+        }
+
+        self.lives -= 1;
+
+        if self.lives == 0 {
+            self.emit(events::Event::GameOver);
+        }
+    }
+    /**
+     * Add points to the score. Crossing a multiple of `SLOWDOWN_SCORE_EVERY` rewards the player
+     * with a "bullet time" slowdown, and every multiple of `extra_life_every` with an extra life.
+     * Neither is awarded in hardcore mode.
+     */
+    fn award(&mut self, points: u32) {
+        let previous = self.score;
+        self.score += points;
+
+        if !self.args.hardcore && self.score / SLOWDOWN_SCORE_EVERY > previous / SLOWDOWN_SCORE_EVERY {
+            self.slowdown = self.slowdown_duration();
+        }
+
+        if let Some(every) = self.args.extra_life_interval() {
+            while self.score >= self.extra_life_threshold + every {
+                self.extra_life_threshold += every;
+                self.lives = self.lives.saturating_add(1);
+                self.emit(events::Event::ExtraLife);
+            }
+        }
+    }
+    /** Frames left on a time attack's clock (see `Arguments.time_limit`), `None` when there's no clock. */
+    fn time_left(&self) -> Option<u64> {
+        let limit = self.args.time_limit? as u64 * Pacer::rate(self.args.sim_rate) as u64;
+        return Some(limit.saturating_sub(self.clock));
+    }
+    /** Length, in frames, of the "3, 2, 1, GO" countdown before the game starts. */
+    fn countdown_duration(&self) -> u16 {
+        return (Pacer::rate(self.args.sim_rate) * (COUNTDOWN_SECONDS + 1)) as u16;
+    }
+    /** Frames the title screen idles for before the attract-mode demo starts. */
+    fn attract_delay(&self) -> u32 {
+        return Pacer::rate(self.args.sim_rate) * ATTRACT_SECONDS;
+    }
+    /** Length, in frames, of the breather after a cleared wave. */
+    fn interlude_duration(&self) -> u16 {
+        return (Pacer::rate(self.args.sim_rate) * WAVE_CLEARED_SECONDS) as u16;
+    }
+    /** Length, in frames, of a "bullet time" slowdown. */
+    fn slowdown_duration(&self) -> u16 {
+        return (Pacer::rate(self.args.sim_rate) * SLOWDOWN_SECONDS) as u16;
+    }
+    /**
+     * Frames between two steps of something happening every `base` frames,
+     * stretched by `SLOWDOWN_FACTOR` while a "bullet time" slowdown is active.
+     */
+    fn cadence(&self, base: u8) -> u64 {
+        let base = base.max(1) as u64;
+        if self.slowdown > 0 {
+            return base * SLOWDOWN_FACTOR;
+        }
+
+        return base;
+    }
+    /**
+     * Move the aliens of an entering formation `frame` frames into the entry animation,
+     * dropping them from the top of the play area toward their formation rows.
+     * Once `ENTRY_FRAMES` have passed they're in place and the formation becomes active.
+     */
+    fn advance_formation_entry(&mut self, frame: u16) {
+        let top = self.play_top();
+        let progress = frame.min(ENTRY_FRAMES) as f32 / ENTRY_FRAMES as f32;
+        for alien in self.enemies.iter_mut() {
+            alien.pos.row = top + ((alien.target_row - top) as f32 * progress).round() as u16;
+            alien.y = alien.pos.row as f32;
+        }
+
+        self.formation = match frame >= ENTRY_FRAMES {
+            true => FormationPhase::Active,
+            false => FormationPhase::EnteringFormation { frame },
+        };
+    }
+    /**
+     * Spawn the next wave's formation of aliens at the top of the play area, paced and sized by the `DifficultyCurve`.
+     * It then enters (drops into) formation over `ENTRY_FRAMES` frames.
+     */
+    fn spawn_wave(&mut self) {
+        self.wave += 1;
+        self.respawns.clear();
+        let ramp = if self.args.fixed_difficulty { 1 } else { self.wave };
+        self.wave_params = self.args.difficulty.at(ramp, self.args.enemy_time);
+        self.enemy_direction = 1;
+        self.formation = FormationPhase::EnteringFormation { frame: 0 };
+
+        // Shielded aliens are drawn as regular ones once cracked, so every sprite shares a footprint.
+        let skin_width = self.sprites.as_ref().map_or(0, |pack| pack.width());
+        let width = self.glyphs.alien.width().max(self.glyphs.shielded.width()).max(skin_width) as u16;
+        let spacing = width + 1;
+        let per_row = ((self.size.cols >> 1) / spacing).clamp(1, 10);
+        let top = self.play_top();
+        let rows = self.wave_params.rows;
+        for row in 0..rows {
+            for col in 0..per_row {
+                self.last_alien_id += 1;
+                self.enemies.push(entities::Alien {
+                    id: self.last_alien_id,
+                    pos: entities::Pos::new(top, 2 + col * spacing),
+                    y: top as f32,
+                    width,
+                    // The top row is left clear for the UFO.
+                    target_row: top + 1 + row,
+                    rank: row,
+                    // The front row (closest to the player) is shielded, taking two hits.
+                    hp: if row == rows - 1 { 2 } else { 1 },
+                    // The back row splits, should splitting be enabled.
+                    tier: if self.args.split_aliens && row == 0 && rows > 1 { 2 } else { 1 },
+                    fragment: false,
+                });
+            }
+        }
+
+        self.wave_size = self.enemies.len();
+    }
+    /**
+     * Record the time a presented frame took, engaging safe mode should frames have been too slow for a while.
+     * Returns `true` when it just did, so the caller can slow down the frame rate to match.
+     */
+    fn record_frame(&mut self, frame_time: time::Duration) -> bool {
+        self.frame_times.record(frame_time);
+        if self.args.safe_mode || !self.slow_frames.record(frame_time) {
+            return false;
+        }
+
+        self.engage_safe_mode();
+        return true;
+    }
+    /**
+     * Cut down on output for slow terminals (`--safe-mode`): colorless ASCII without a starfield,
+     * drawn at no more than `SAFE_MODE_FRAME_RATE` frames per second.
+     */
+    fn engage_safe_mode(&mut self) {
+        self.args.safe_mode = true;
+        self.args.no_color = true;
+        self.args.ascii = true;
+        self.args.stars = false;
+        self.args.frame_rate = match self.args.frame_rate {
+            FrameRate::Capped(rate) => FrameRate::Capped(rate.min(SAFE_MODE_FRAME_RATE)),
+            FrameRate::Uncapped => FrameRate::Capped(SAFE_MODE_FRAME_RATE),
+        };
+
+        // The overrides were validated at startup, they won't fail now.
+        self.glyphs = glyphs::GlyphMap::ascii().with_overrides(&self.args.glyphs).unwrap_or_else(|_| glyphs::GlyphMap::ascii());
+        self.args.alien_skin = None;
+        self.sprites = None;
+        self.player.width = self.glyphs.player.width() as u16;
+        self.falling_stars.clear();
+    }
+    /** The play area as it would be drawn right now, cell by cell (see `render::Grid`), for tests to assert on. */
+    #[cfg(test)]
+    pub fn compose_grid(&self) -> render::Grid {
+        return render::compose_grid(self, &mut render::RowBuffer::default()).clone();
+    }
+    /** What the session came down to so far, as `start()` returns it once it ends. */
+    pub fn outcome(&self) -> Outcome {
+        let result = match self.phase {
+            Phase::Won => GameResult::Won,
+            Phase::GameOver => GameResult::Lost,
+            _ => GameResult::Quit,
+        };
+
+        return Outcome { result, score: self.score, wave: self.wave, stats: self.stats.clone() };
+    }
+    /** Which keys the player's input is mapped with, for whatever's on screen. */
+    pub fn keymap(&self) -> input::Keymap {
+        if self.is_paused() {
+            return input::Keymap::Menu;
+        }
+        if self.initials.is_some() {
+            return input::Keymap::Text;
+        }
+
+        return input::Keymap::Game;
+    }
+    /** Whether a game has started and hasn't ended yet, what's saved on quitting (see `save`). */
+    pub fn in_progress(&self) -> bool {
+        return matches!(self.phase, Phase::Countdown(_) | Phase::Playing | Phase::Paused(_) | Phase::WaveCleared(_) | Phase::Quit);
+    }
+    /** Whether the game is frozen on the pause menu. */
+    pub fn is_paused(&self) -> bool {
+        return matches!(self.phase, Phase::Paused(_));
+    }
+    /** Whether the terminal (not the arena) is at least `min_rows` tall and `min_cols` wide. */
+    pub fn is_playable(&self) -> bool {
+        return self.terminal.rows >= self.args.min_rows && self.terminal.cols >= self.args.min_cols;
+    }
+    /** Rows taken up by the HUD, its message line plus a divider (and the ticker, if shown) when there's room for them. */
+    fn hud_rows(&self) -> u16 {
+        if self.size.rows <= 10 {
+            return 1;
+        }
+
+        return if self.args.ticker { 3 } else { 2 };
+    }
+    /** The first row of the play area, below the HUD when it's placed on top. */
+    fn play_top(&self) -> u16 {
+        return match self.args.hud_position {
+            HudPosition::Top => self.hud_rows(),
+            HudPosition::Bottom => 0,
+        };
+    }
+    /** The row just past the end of the play area, where a bottom HUD starts. */
+    fn play_bottom(&self) -> u16 {
+        return match self.args.hud_position {
+            HudPosition::Top => self.size.rows,
+            HudPosition::Bottom => self.size.rows.saturating_sub(self.hud_rows()),
+        };
+    }
+    /** The row the player's ship sits on, the last row of the play area. */
+    fn player_row(&self) -> u16 {
+        return self.play_bottom().saturating_sub(1);
+    }
+    /**
+     * Column the ship fires from, the sprite's origin (its tip), still within the arena
+     * should the ship hug its edge.
+     */
+    fn muzzle_col(&self) -> u16 {
+        return (self.player.pos.col + self.glyphs.player_origin).min(self.size.cols.saturating_sub(1));
+    }
+    /**
+     * The row an alien reaching invades, losing the game. The player's row, unless `invasion_row_offset`
+     * keeps the formation a few rows above the ship (never above the play area though).
+     */
+    fn invasion_row(&self) -> u16 {
+        return self.player_row().saturating_sub(self.args.invasion_row_offset).max(self.play_top());
+    }
+    /** Return all enemies (aliens) in the current GameState instance. */
+    pub fn enemies(&self) -> &[entities::Alien] {
+        return &self.enemies;
+    }
+    /** Return all projectiles in the current GameState instance. */
+    pub fn projectiles(&self) -> &[entities::Projectile] {
+        return &self.projectiles;
+    }
+    /** Return the player of the current GameState instance. */
+    pub fn player(&self) -> &entities::Player {
+        return &self.player;
+    }
+    /**
+     * What occupies the given cell, if anything. Where several entities share a cell, the one drawn in front
+     * wins: the player, then projectiles, then aliens and the UFO, then stars.
+     */
+    pub fn entity_at(&self, pos: entities::Pos) -> Option<EntityRef> {
+        if pos.row == self.player.pos.row && self.player.occupies(pos.col) {
+            return Some(EntityRef::Player);
+        }
+        if let Some(index) = self.projectiles.iter().position(|projectile| projectile.pos == pos) {
+            return Some(EntityRef::Projectile(index));
+        }
+        if let Some(index) = self.enemies.iter().position(|alien| alien.occupies(pos.row, pos.col)) {
+            return Some(EntityRef::Alien(index));
+        }
+        if self.ufo.as_ref().is_some_and(|ufo| ufo.occupies(pos.row, pos.col)) {
+            return Some(EntityRef::Ufo);
+        }
+        if let Some(index) = self.falling_stars.iter().position(|star| star.pos == pos) {
+            return Some(EntityRef::Star(index));
+        }
+
+        return None;
+    }
+}
+
+/** Kind of entity found by `GameState::entity_at`, along with its index where there may be several. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityRef {
+    Player,
+    Alien(usize),
+    Ufo,
+    Projectile(usize),
+    Star(usize),
+}
+
+/** Where the HUD (status bar) is drawn, the play area takes up the remaining rows. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HudPosition {
+    Top,
+    Bottom,
+}
+
+/** Which rates the HUD's status line shows, see `render::status_line`. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateDisplay {
+    /** Frames drawn per second, as measured. */
+    Render,
+    /** Simulation steps per second, as paced. */
+    Sim,
+    Both,
+}
+
+/** What happens when the player's ship runs into the edge of the screen. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovementMode {
+    /** Stop at the edge. */
+    Clamp,
+    /** Exit one side, reappear on the other. */
+    Wrap,
+}
+
+/** Which aliens are allowed to fire. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FireRule {
+    /** Only the lowest alien of each column (the classic rule), so shots never pass through other aliens. */
+    FrontRow,
+    /** Any alien. */
+    Any,
+}
+
+/** How enemy projectiles travel down the screen. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulletPattern {
+    /** Straight down. */
+    Straight,
+    /** Zig-zagging a column to either side, alternating with every step. */
+    ZigZag,
+}
+
+/** How often the screen is drawn. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameRate {
+    /** At most this many frames per second. */
+    Capped(u16),
+    /** As fast as possible, a frame every pass of the main loop. */
+    Uncapped,
+}
+
+impl FrameRate {
+    /** Time between two frames, zero when uncapped. */
+    pub fn target(&self) -> time::Duration {
+        return match self {
+            FrameRate::Capped(rate) => time::Duration::from_nanos(1_000_000_000_u64 / (*rate).max(1) as u64),
+            FrameRate::Uncapped => time::Duration::ZERO,
+        };
+    }
+    /** Frames per second, or about the most that'd ever be reported when uncapped. */
+    pub fn per_second(&self) -> u32 {
+        return match self {
+            FrameRate::Capped(rate) => (*rate).max(1) as u32,
+            FrameRate::Uncapped => MAX_REPORTED_FPS as u32,
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Arguments {
+    /** Render rate, frames drawn per second. */
+    pub frame_rate: FrameRate,
+    /** Simulation rate, game steps per second. `bullet_time` and `enemy_time` are counted in steps. */
+    pub sim_rate: Option<u8>,
+    /** Fire cooldown, steps between two player shots. */
+    pub bullet_time: u8,
+    /** Rows a player projectile travels per step (its initial speed, under `gravity`). */
+    pub projectile_speed: u8,
+    /** Rows per step player projectiles slow down by every step, arcing back down. `0` flies them straight. */
+    pub gravity: f32,
+    /** Rows an enemy projectile travels per step (enemy shots stepping every other frame). */
+    pub enemy_projectile_speed: u8,
+    /** How enemy projectiles travel. */
+    pub enemy_bullet_pattern: BulletPattern,
+    /** Frames between two marches (and fire attempts) of the first wave, see `difficulty`. */
+    pub enemy_time: u8,
+    /** Fewest frames between two marches, however few aliens are left to speed the formation up. */
+    pub min_enemy_time: u8,
+    /** Back-row aliens split into two fragments when destroyed, which have to be shot down too. */
+    pub split_aliens: bool,
+    /** How later waves ramp up (pace, fire rate and formation size). */
+    pub difficulty: difficulty::DifficultyCurve,
+    /** Points awarded for kills and cleared waves. */
+    pub scoring: scoring::ScoreConfig,
+    /** Abort on runtime errors (returning them from `start()`), rather than printing them and carrying on. */
+    pub panic_on_errors: bool,
+    pub hud_position: HudPosition,
+    /** Times per second the HUD's status line is refreshed, `None` refreshing it with every frame drawn. */
+    pub hud_rate: Option<u8>,
+    /** Show the rates (see `rate_display`) on the status line, `false` for a clean HUD of only the game itself. */
+    pub show_debug: bool,
+    pub rate_display: RateDisplay,
+    /** Show a ticker of the latest notable events on the HUD, below the status line (given the room). */
+    pub ticker: bool,
+    /** Start with a `min_rows`x`min_cols` arena, growing every time this many points are scored. `None` plays on the whole terminal. */
+    pub arena_growth: Option<u32>,
+    /** Alien art pack, `None` for the glyph map's `alien` sprite. */
+    pub alien_skin: Option<sprites::Skin>,
+    /** Draw the starfield in the background at all. */
+    pub stars: bool,
+    /** Stars in the background, per thousand terminal cells. */
+    pub star_density: f32,
+    /** Keep the starfield drifting behind the pause menu, rather than freezing it along with everything else. */
+    pub stars_animate_while_paused: bool,
+    /** Debugging aid, the player ignores enemy projectiles and aliens (god mode). */
+    pub invincible: bool,
+    /** Print a report of frame times (percentiles and a histogram) on exit. */
+    pub profile: bool,
+    /** Dim every other row like the scanlines of an old CRT, unless colors (or unicode) are off. */
+    pub crt: bool,
+    /** Cut down on output for slow terminals, see `GameState::engage_safe_mode`. Also engaged when frames are too slow. */
+    pub safe_mode: bool,
+    /** Show the score and wave in the terminal's window (tab) title, restoring the previous title on exit. */
+    pub set_title: bool,
+    /** Control the ship with the mouse (where the terminal supports it), alongside the keyboard. */
+    pub mouse: bool,
+    /** Play with a gamepad (d-pad or left stick, `A` to fire, start to pause), alongside the keyboard. Needs the `gamepad` feature. */
+    pub gamepad: bool,
+    /** Which aliens may fire. */
+    pub fire_rule: FireRule,
+    /** Most enemy projectiles in flight at once, `None` for no limit. */
+    pub max_enemy_projectiles: Option<u16>,
+    /** Frames between the UFO's chances to appear, `0` to never have it appear. */
+    pub ufo_interval: u32,
+    /** Chance (`0.0` to `1.0`) of the UFO appearing, every `ufo_interval` frames. */
+    pub ufo_chance: f32,
+    /** Columns the UFO flies per step. */
+    pub ufo_speed: u8,
+    /** Lives the player starts with. */
+    pub lives: u8,
+    /** Rows above the player's ship the formation invades at, so it never has to overlap the ship (or the HUD). */
+    pub invasion_row_offset: u16,
+    /** Aliens invading cost a life (and are removed) rather than the whole game. */
+    pub invasion_costs_life: bool,
+    /** Award an extra life every time the score crosses a multiple of this, `None` to never award any. */
+    pub extra_life_every: Option<u32>,
+    /**
+     * Permadeath: a single life and no extra lives, whatever `lives` and `extra_life_every` say, nor any
+     * "bullet time" slowdowns. Stats and scores of hardcore runs are filed separately, see `stats_log_path`.
+     */
+    pub hardcore: bool,
+    /**
+     * Practice, for aim and dodging: unlimited lives, an invasion only restarting the wave,
+     * and scores that are kept track of but never saved (neither logged nor put on the leaderboard).
+     */
+    pub practice: bool,
+    /** Steps after which destroyed aliens respawn, `None` for them to stay destroyed. */
+    pub respawn_delay: Option<u16>,
+    /** Keep every wave at the first wave's difficulty, rather than ramping up along `difficulty`. */
+    pub fixed_difficulty: bool,
+    /** Play as if the terminal was this size (`rows`, `cols`), whatever its actual size. For testing and recording. */
+    pub force_size: Option<(u16, u16)>,
+    /** Smallest terminal (in rows) the game can be played in. */
+    pub min_rows: u16,
+    /** Smallest terminal (in columns) the game can be played in. */
+    pub min_cols: u16,
+    pub movement_mode: MovementMode,
+    /** How key presses buffered between two simulation steps are resolved. */
+    pub input_mode: input::InputMode,
+    /** Most actions and movement key presses buffered between two simulation steps, older ones being dropped (see `InputQueue`). */
+    pub input_capacity: usize,
+    /**
+     * Milliseconds a movement key counts as held after its latest press (or OS key-repeat), on terminals that
+     * don't report key releases (see `HeldKeys`).
+     */
+    pub hold_timeout: u16,
+    /** Number of waves in a finite campaign, `None` for endless waves. */
+    pub total_waves: Option<u16>,
+    /** Time attack: seconds of play to score as much as possible in, the game ending once they run out (lives or not). */
+    pub time_limit: Option<u16>,
+    /** Force pure-ASCII glyphs, regardless of what the terminal appears to support. */
+    pub ascii: bool,
+    /** Disable all colors, also set by the `NO_COLOR` environment variable. */
+    pub no_color: bool,
+    /** Colors the terminal can display, detected from `COLORTERM` / `TERM` unless overridden (`--color-depth`). */
+    pub color_depth: colors::ColorDepth,
+    /** Shift the hue of the aliens with every wave, off for a steady palette (`--no-wave-colors`). */
+    pub wave_colors: bool,
+    /** Fills every empty cell of the screen, a single printable, single-cell character. */
+    pub background_char: char,
+    /** Glyph overrides by name (`--glyph alien=M`), applied on top of the detected set. */
+    pub glyphs: Vec<(String, String)>,
+    /** Attract mode, let the AI (`ai::decide`) control the player. */
+    pub demo: bool,
+    /** Seed for the game's RNG, `None` seeds from entropy. */
+    pub seed: Option<u64>,
+    /** How strongly enemy fire favours aliens in/near the player's column, `0` for no preference. */
+    pub fire_bias: f32,
+    /** Append the session stats of every finished game to this file. */
+    pub stats_log: Option<PathBuf>,
+    /** Keep the best scores (with the player's initials) in this file, `None` to not keep any. */
+    pub leaderboard: Option<PathBuf>,
+    /** Save a game quit mid-play to this file, to continue it next time (see `save`). `None` to not save any. */
+    pub save: Option<PathBuf>,
+    /** Config file the arguments were read from, below the environment and the flags (see `cli`). */
+    pub config: Option<PathBuf>,
+    /** Run the AI headless for this many frames, print the stats and exit (`--benchmark <frames>`). */
+    pub benchmark: Option<u32>,
+}
+
+impl Default for Arguments {
+    fn default() -> Self {
+        return Arguments {
+            frame_rate: FrameRate::Capped(8),
+            sim_rate: Some(8),
+            bullet_time: 2,
+            projectile_speed: 1,
+            gravity: 0_f32,
+            enemy_projectile_speed: 1,
+            enemy_bullet_pattern: BulletPattern::Straight,
+            enemy_time: 8,
+            min_enemy_time: 2,
+            split_aliens: false,
+            difficulty: difficulty::DifficultyCurve::default(),
+            scoring: scoring::ScoreConfig::default(),
+            panic_on_errors: true,
+            hud_position: HudPosition::Top,
+            hud_rate: None,
+            show_debug: true,
+            rate_display: RateDisplay::Render,
+            ticker: false,
+            arena_growth: None,
+            alien_skin: None,
+            stars: true,
+            star_density: 2.0,
+            stars_animate_while_paused: false,
+            invincible: false,
+            profile: false,
+            crt: false,
+            safe_mode: false,
+            set_title: false,
+            mouse: false,
+            gamepad: false,
+            fire_rule: FireRule::FrontRow,
+            max_enemy_projectiles: Some(4),
+            ufo_interval: 160,
+            ufo_chance: 0.5,
+            ufo_speed: 1,
+            lives: 3,
+            invasion_row_offset: 0,
+            invasion_costs_life: false,
+            force_size: None,
+            extra_life_every: None,
+            hardcore: false,
+            practice: false,
+            respawn_delay: None,
+            fixed_difficulty: false,
+            min_rows: 20,
+            min_cols: 40,
+            movement_mode: MovementMode::Clamp,
+            input_mode: input::InputMode::Queue,
+            input_capacity: 16,
+            hold_timeout: input::HOLD_TIMEOUT.as_millis() as u16,
+            total_waves: None,
+            time_limit: None,
+            ascii: false,
+            no_color: colors::no_color_env(),
+            color_depth: colors::ColorDepth::detect(),
+            wave_colors: true,
+            background_char: ' ',
+            glyphs: vec!(),
+            demo: false,
+            seed: None,
+            fire_bias: 1_f32,
+            stats_log: None,
+            leaderboard: None,
+            save: None,
+            config: None,
+            benchmark: None,
+        };
+    }
+}
+
+impl Arguments {
+    /** Lives the player starts with, a single one in hardcore mode. */
+    pub fn starting_lives(&self) -> u8 {
+        return if self.hardcore { 1 } else { self.lives };
+    }
+    /** Score interval extra lives are awarded at, never in hardcore mode. */
+    pub fn extra_life_interval(&self) -> Option<u32> {
+        return self.extra_life_every.filter(|every| !self.hardcore && *every > 0);
+    }
+    /**
+     * Where the stats of a finished game are appended, hardcore runs being filed separately (`<stats_log>.hardcore`).
+     * None in practice or a demo.
+     */
+    pub fn stats_log_path(&self) -> Option<PathBuf> {
+        return self.stats_log.as_deref().filter(|_| !self.practice && !self.demo).map(|path| self.filed(path));
+    }
+    /** Where the leaderboard is kept, hardcore runs having one of their own (`<leaderboard>.hardcore`). None in practice. */
+    pub fn leaderboard_path(&self) -> Option<PathBuf> {
+        return self.leaderboard.as_deref().filter(|_| !self.practice).map(|path| self.filed(path));
+    }
+    /** Where a game quit mid-play is saved, hardcore runs having one of their own (`<save>.hardcore`). None in a demo. */
+    pub fn save_path(&self) -> Option<PathBuf> {
+        return self.save.as_deref().filter(|_| !self.demo).map(|path| self.filed(path));
+    }
+    /** The file to keep records of this kind of run in, given the one for regular runs. */
+    fn filed(&self, path: &std::path::Path) -> PathBuf {
+        if !self.hardcore {
+            return path.to_path_buf();
+        }
+
+        let mut name = path.as_os_str().to_owned();
+        name.push(".hardcore");
+        return PathBuf::from(name);
+    }
+}
+
+/**
+ * Keeps the simulation and the rendering on their own, independent, cadences.
+ * The simulation advances at `sim_rate` steps/sec, the screen is drawn at `frame_rate` frames/sec.
+ *
+ * Time is accumulated as an integer `Duration`, and steps are derived from the total elapsed time
+ * rather than summed up step-by-step, so no rounding error can build up over long sessions.
+ */
+#[derive(Debug)]
+struct Pacer {
+    sim_rate: u32,
+    frame_rate: FrameRate,
+    elapsed: time::Duration,
+    sim_steps: u64,
+    frames: u64,
+    last_frame: time::Duration,
+}
+
+/** Shortest wait the main loop sleeps through, spinning through anything shorter (sleeps tend to overshoot). */
+const SPIN_THRESHOLD: time::Duration = time::Duration::from_millis(2);
+
+impl Pacer {
+    fn new(sim_rate: Option<u8>, frame_rate: FrameRate) -> Self {
+        return Pacer {
+            sim_rate: Pacer::rate(sim_rate),
+            frame_rate,
+            elapsed: time::Duration::ZERO,
+            sim_steps: 0_u64,
+            frames: 0_u64,
+            last_frame: time::Duration::ZERO,
+        };
+    }
+    /** Steps per second for the given (optional) rate. */
+    fn rate(rate: Option<u8>) -> u32 {
+        return match rate {
+            Some(rate) if rate > 0 => rate as u32,
+            _ => u8::MAX as u32, // "Uncapped".
+        };
+    }
+    /** Total number of steps that should have happened at `rate` after `elapsed` time. */
+    fn due(elapsed: time::Duration, rate: u32) -> u64 {
+        return (elapsed.as_nanos() * rate as u128 / 1_000_000_000_u128) as u64;
+    }
+    /** Time at which the `count`th step at `rate` is due. */
+    fn due_at(count: u64, rate: u32) -> time::Duration {
+        return time::Duration::from_nanos((count as u128 * 1_000_000_000_u128).div_ceil(rate.max(1) as u128) as u64);
+    }
+    /**
+     * Feed the pacer `elapsed` time. Returns how many simulation steps are due,
+     * and the time since the last render if a new frame should be drawn (always, when uncapped).
+     */
+    fn advance(&mut self, elapsed: time::Duration) -> (u32, Option<time::Duration>) {
+        self.elapsed += elapsed;
+
+        let sim_steps = Pacer::due(self.elapsed, self.sim_rate);
+        let steps = (sim_steps - self.sim_steps) as u32;
+        self.sim_steps = sim_steps;
+
+        let frames = match self.frame_rate {
+            FrameRate::Capped(rate) => Pacer::due(self.elapsed, rate.max(1) as u32),
+            FrameRate::Uncapped => self.frames + 1,
+        };
+        if frames == self.frames {
+            return (steps, None);
+        }
+
+        let frame_time = self.elapsed - self.last_frame;
+        self.frames = frames;
+        self.last_frame = self.elapsed;
+        return (steps, Some(frame_time));
+    }
+    /** Time left until the next simulation step or frame is due, zero when uncapped. */
+    fn until_due(&self) -> time::Duration {
+        let step = Pacer::due_at(self.sim_steps + 1, self.sim_rate);
+        let frame = self.last_frame + self.frame_rate.target();
+        return step.min(frame).saturating_sub(self.elapsed);
+    }
+    /** Wait for whatever's due next, sleeping through most of it and spinning through the rest to stay on time. */
+    fn wait(&self) {
+        let wait = self.until_due();
+        if wait > SPIN_THRESHOLD {
+            std::thread::sleep(wait - SPIN_THRESHOLD);
+        }
+    }
+}
+
+/** Highest framerate the HUD will ever report, anything faster is effectively "uncapped". */
+const MAX_REPORTED_FPS: u16 = 999;
+
+/**
+ * Compute the framerate from the time a frame took, guarding against near-zero frame times
+ * (uncapped rates) which would otherwise overflow the `u16` or report infinity.
+ */
+fn measure_fps(frame_time: time::Duration) -> u16 {
+    let nanos = frame_time.as_nanos();
+    if nanos == 0 {
+        return MAX_REPORTED_FPS;
+    }
+
+    let fps = (1_000_000_000_u128 + (nanos >> 1)) / nanos;
+    return fps.min(MAX_REPORTED_FPS as u128) as u16;
+}
+
+/** How a session ended, when it ended cleanly. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    /** The player quit (or a benchmark ran to completion) before the game was decided. */
+    Quit,
+    /** The player quit after winning the campaign. */
+    Won,
+    /** The player quit after losing. */
+    Lost,
+}
+
+/** What a session came down to, returned by `start()` for the caller to print, save or send wherever. */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub result: GameResult,
+    pub score: u32,
+    /** The wave the session ended on. */
+    pub wave: u16,
+    pub stats: stats::SessionStats,
+}
+
+impl Outcome {
+    /**
+     * Process exit code for the outcome, always zero: however the game went, the session ended cleanly.
+     * Errors have codes of their own (see `GameError::exit_code`).
+     */
+    pub fn exit_code(&self) -> i32 {
+        return 0;
+    }
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let result = match self.result {
+            GameResult::Quit => "Quit",
+            GameResult::Won => "Won the campaign",
+            GameResult::Lost => "Game over",
+        };
+
+        writeln!(f, "{result}! Score: {} | Wave: {}", self.score, self.wave)?;
+        return write!(
+            f,
+            "Shots fired: {} | Hits: {} ({:.1}%) | Aliens killed: {} | Max combo: {}",
+            self.stats.shots_fired,
+            self.stats.hits,
+            self.stats.accuracy(),
+            self.stats.aliens_killed,
+            self.stats.max_combo
+        );
+    }
+}
+
+/** Unrecoverable errors ending a session early (or keeping it from starting at all), each with its own exit code. */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameError {
+    /** Invalid configuration, such as bad flags (or variables, or config file lines) and glyph overrides. */
+    Config(String),
+    /** The terminal couldn't be measured, put into raw mode or drawn to. */
+    Terminal(String),
+    /** Reading input failed. */
+    Input(String),
+    /** Writing to a file (the stats log) failed. */
+    Io(String),
+}
+
+impl GameError {
+    /** Process exit code for the error. */
+    pub fn exit_code(&self) -> i32 {
+        return match self {
+            GameError::Config(_) => 2,
+            GameError::Terminal(_) => 3,
+            GameError::Input(_) => 4,
+            GameError::Io(_) => 5,
+        };
+    }
+}
+
+impl std::fmt::Display for GameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            GameError::Config(message)
+            | GameError::Terminal(message)
+            | GameError::Input(message)
+            | GameError::Io(message) => write!(f, "{message}"),
+        };
+    }
+}
+
+/**
+ * Starts the game by initiating the loop, returning how it ended once the player quits,
+ * or once `stop` is set (letting a host, e.g. another thread, request a clean shutdown).
+ * Runtime errors are only returned when `panic_on_errors` is set, otherwise they're printed and the game carries on.
+ */
+pub fn start(args: Arguments, stop: &AtomicBool) -> Result<Outcome, GameError> {
+    if let Some(frames) = args.benchmark {
+        let (report, outcome) = benchmark::run(args, frames);
+        // Piped into `head` for example, the pipe may well be closed already. No reason to panic over.
+        let _ = writeln!(io::stdout(), "{report}");
+        return Ok(outcome);
+    }
+
+    let mut state = init_state(args)?;
+    let guard = input::TerminalGuard::new(state.args.mouse, state.args.set_title)
+        .map_err(|e| GameError::Terminal(format!("Failed to start! Couldn't enable raw mode, {e}")))?;
+    // Scanlines are dimmed through the same escapes as colors, so plain terminals go without.
+    let crt = state.args.crt && !state.args.no_color && !state.args.ascii;
+    let renderer = render::Renderer::new(io::stdout(), render::ScreenMode::detect()).with_crt(crt);
+    let outcome = run_loop(&mut state, renderer, guard.reports_releases(), stop);
+
+    // Restore the terminal before printing anything else.
+    drop(guard);
+    let outcome = outcome?;
+    finish(&state, &mut io::stdout());
+    if let Some(path) = state.args.save_path() {
+        save::settle(&state, &path).map_err(|e| GameError::Io(format!("Failed to save the game to '{}', {e}", path.display())))?;
+    }
+    return Ok(outcome);
+}
+
+/**
+ * A game ready to be played: glyphs detected, fitted to the terminal, with the leaderboard (and saved game) loaded
+ * and a demo's recording ready to play back. Waiting on the title screen, unless it's a demo.
+ */
+fn init_state(args: Arguments) -> Result<GameState, GameError> {
+    // A demo plays back the bundled recording, at the seed and size it was recorded at.
+    let mut args = args;
+    let demo = if args.demo { Some(replay::Replay::demo().map_err(GameError::Config)?) } else { None };
+    if let Some(demo) = &demo {
+        args.seed = Some(demo.seed);
+        args.force_size = Some((demo.rows, demo.cols));
+    }
+
+    if !args.ascii && !args.safe_mode && !glyphs::supports_unicode() {
+        eprintln!("Warning! Terminal doesn't look unicode-capable, falling back to ASCII glyphs (silence with '--ascii').");
+    }
+
+    let glyphs = glyphs::GlyphMap::detect(args.ascii)
+        .with_overrides(&args.glyphs)
+        .map_err(GameError::Config)?;
+
+    let mut state = GameState::new(args.clone());
+    state.glyphs = glyphs;
+    state.evaluate_state().map_err(|e| GameError::Terminal(format!("Failed to start! {e}")))?;
+    let size = Size { rows: state.terminal.rows, cols: state.terminal.cols };
+    state.reset(args, size);
+    if state.args.safe_mode {
+        state.engage_safe_mode();
+    }
+    if let Some(path) = state.args.leaderboard_path() {
+        let leaderboard = leaderboard::Leaderboard::load(&path)
+            .map_err(|e| GameError::Io(format!("Failed to load the leaderboard '{}', {e}", path.display())))?;
+        state.leaderboard = Some(leaderboard);
+    }
+    if let Some(path) = state.args.save_path() {
+        state.saved = save::load(&path).map_err(|e| GameError::Io(format!("Failed to load the saved game '{}', {e}", path.display())))?;
+    }
+    state.replay = demo.map(replay::Playback::new);
+    if !state.args.demo {
+        state.phase = Phase::Title; // Nobody to press start in a demo.
+    }
+
+    return Ok(state);
+}
+
+/**
+ * Play the game, reading input and presenting frames through `renderer`, until the player quits, whatever reads
+ * the frames goes away, or `stop` is set. Key releases are only tracked should the terminal report them (`releases`).
+ */
+fn run_loop<W: io::Write>(state: &mut GameState, renderer: render::Renderer<W>, releases: bool, stop: &AtomicBool) -> Result<Outcome, GameError> {
+    let panic_on_errors = state.args.panic_on_errors;
+    let mut renderer = renderer;
+    let mut pacer = Pacer::new(state.args.sim_rate, state.args.frame_rate);
+    let mut hud = render::Hud::new(state.args.frame_rate, state.args.hud_rate);
+    let mut rows = render::RowBuffer::default();
+    let mut title = state.args.set_title.then(render::WindowTitle::default);
+    let mut buffer = input::InputQueue::new(state.args.input_capacity);
+    let mut held = input::HeldKeys::new(releases).with_timeout(time::Duration::from_millis(state.args.hold_timeout as u64));
+    let mut pad = gamepad::Gamepad::default();
+    #[cfg(feature = "gamepad")]
+    let mut pads = if state.args.gamepad { Some(gamepad::Pads::new().map_err(GameError::Input)?) } else { None };
+
+    // Either abort with the error, or report it and carry on.
+    let report = |error: GameError| -> Result<(), GameError> {
+        if panic_on_errors {
+            return Err(error);
+        }
+
+        print!("Error! {error}\r\n");
+        return Ok(());
+    };
+
+    let mut t = time::Instant::now();
+    let outcome = loop {
+        if stop.load(Ordering::Relaxed) {
+            break state.outcome();
+        }
+
+        // Buffer input until the next simulation step.
+        if let Err(e) = input::poll(&mut buffer, state.keymap()) {
+            report(GameError::Input(format!("Cought an error reading input, {e}")))?;
+        }
+        #[cfg(feature = "gamepad")]
+        for event in pads.as_mut().map(gamepad::Pads::poll).unwrap_or_default() {
+            pad.handle(event, state.keymap(), &mut buffer, time::Instant::now());
+        }
+
+        if buffer.contains(&Action::Quit) || state.phase == Phase::Quit {
+            break state.outcome();
+        }
+
+        // Increment the pacer by elapsed time.
+        let now = time::Instant::now();
+        let (steps, frame_time) = pacer.advance(now - t);
+        t = now;
+        for _ in 0..steps {
+            let queued = buffer.drain(&mut held);
+            let mut step: Vec<Action> = held.movement(now);
+            step.append(&mut pad.movement(now));
+            step.extend(queued);
+            let actions = input::resolve(&step, state.args.input_mode);
+
+            // Run an iteration of the game loop.
+            if let Err(error) = game_loop(state, actions) {
+                report(error)?;
+            }
+        }
+
+        if let Some(frame_time) = frame_time {
+            let meassure: u16 = measure_fps(frame_time);
+            if state.record_frame(frame_time) {
+                // Safe mode just engaged, slow down to match.
+                pacer.frame_rate = state.args.frame_rate;
+                hud = render::Hud::new(state.args.frame_rate, state.args.hud_rate);
+                renderer = renderer.with_crt(false);
+            }
+
+            if let Some(sequence) = title.as_mut().and_then(|title| title.update(state.score, state.wave)) {
+                if let Err(e) = renderer.queue(&sequence) {
+                    report(GameError::Terminal(format!("Cought an error setting the window title, {e}")))?;
+                }
+            }
+
+            let status = hud.status(|| render::status_line(meassure, state));
+            match renderer.present(&render::render(status, state, &mut rows)) {
+                Ok(render::Presented::Warned) => eprint!("Warning! {}\r\n", renderer.warning().unwrap_or_default()),
+                Ok(render::Presented::Drawn) => {}
+                Ok(render::Presented::Closed) => break state.outcome(), // Nobody's watching, as good as quitting.
+                Err(e) => report(GameError::Terminal(format!("Cought an error rendering, {e}")))?,
+            }
+        }
+
+        pacer.wait();
+    };
+
+    return Ok(outcome);
+}
+
+/** Wrap up a finished session, reporting frame times to `out` should they have been profiled (`--profile`). */
+fn finish(state: &GameState, out: &mut impl io::Write) {
+    if state.args.profile {
+        for line in state.frame_times.report() {
+            let _ = writeln!(out, "{line}");
+        }
+    }
+}
+
+/** Whether the action plays the game (as opposed to navigating menus or quitting). */
+fn is_gameplay(action: &Action) -> bool {
+    return matches!(action, Action::MoveLeft | Action::MoveRight | Action::MoveTo(_) | Action::Fire);
+}
+
+/**
+ * Main game loop.
+ * Runs a single simulation step, invoked `sim_rate` times per second independently of rendering.
+ */
+fn game_loop(state: &mut GameState, input: Vec<Action>) -> Result<(), GameError> {
+    // Evaluate / Re-calculate game-state.
+    // This validates enemy, player and projectile position in relation to current terminal size.
+    state.evaluate_state().map_err(GameError::Terminal)?;
+    if !state.is_playable() {
+        return Ok(()); // Hold everything until the terminal is large enough again.
+    }
+
+    let actions = if state.attract && input.iter().any(is_gameplay) {
+        // Somebody's at the controls, the attract-mode demo makes way for them.
+        state.end_attract();
+        vec![]
+    } else if state.args.demo {
+        // The recording plays (the AI taking over once it runs out), but the pause menu is still the user's to drive.
+        let mut actions: Vec<Action> = input.into_iter().filter(|action| !is_gameplay(action)).collect();
+        if !state.is_paused() {
+            match state.replay.as_mut().and_then(replay::Playback::next_step) {
+                Some(mut step) => actions.append(&mut step),
+                None => actions.append(&mut ai::decide(state)),
+            }
+        }
+        actions
+    } else {
+        input
+    };
+
+    state.tick(&actions);
+
+    if let Some(path) = &state.args.stats_log_path() {
+        if state.events.contains(&events::Event::GameOver) || state.events.contains(&events::Event::CampaignWon) {
+            if let Err(e) = state.stats.append_to(path) {
+                return Err(GameError::Io(format!("Failed to append stats to '{}', {e}", path.display())));
+            }
+        }
+    }
+
+    if let (Some(path), Some(leaderboard)) = (state.args.leaderboard_path(), &state.leaderboard) {
+        if state.events.iter().any(|event| matches!(event, events::Event::HighScore { .. })) {
+            if let Err(e) = leaderboard.save(&path) {
+                return Err(GameError::Io(format!("Failed to save the leaderboard to '{}', {e}", path.display())));
+            }
+        }
+    }
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * A fresh game in a terminal of `rows`x`cols`, past its countdown. Seeded (and colorless), so that
+     * every run plays out (and renders) the same.
+     */
+    pub fn test_state(rows: u16, cols: u16) -> GameState {
+        let args = Arguments { seed: Some(7), no_color: true, ..Arguments::default() };
+        let mut state = GameState::new(args.clone());
+        state.reset(args, Size { rows, cols });
+        state.phase = Phase::Playing;
+        return state;
+    }
+
+    /** Tick once for every frame of `script`, each frame being the actions applied on it. */
+    pub fn run_script(state: &mut GameState, script: &[&[Action]]) {
+        for actions in script {
+            state.tick(actions);
+        }
+    }
+
+    /** Tick `frames` times without any input. */
+    pub fn run_idle(state: &mut GameState, frames: usize) {
+        for _ in 0..frames {
+            state.tick(&[]);
+        }
+    }
+
+    /** Compose a frame the way `start()` presents it, one string per terminal row. */
+    pub fn capture(state: &GameState) -> Vec<String> {
+        let status = render::status_line(0, state);
+        return render::render(&status, state, &mut render::RowBuffer::default()).split("\r\n").map(String::from).collect();
+    }
+
+    #[test]
+    fn empty_cells_are_filled_with_the_background_char() {
+        let mut state = test_state(20, 40);
+        state.args.stars = false;
+        state.args.background_char = '~';
+        run_idle(&mut state, 1);
+
+        let frame = capture(&state);
+        let hud = state.hud_rows() as usize;
+        for row in state.play_top()..state.play_bottom() {
+            let line: Vec<char> = frame[hud + (row - state.play_top()) as usize].chars().collect();
+            for col in 0..state.size.cols {
+                if state.entity_at(entities::Pos::new(row, col)).is_none() {
+                    assert_eq!(line[col as usize], '~', "cell {row},{col}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn only_the_hud_is_divided_from_the_play_area() {
+        for position in [HudPosition::Top, HudPosition::Bottom] {
+            let mut state = test_state(20, 40);
+            state.args.hud_position = position;
+            state.args.stars = false;
+            state.enemies.clear();
+            state.player.pos.col = 0;
+
+            let frame = capture(&state);
+            let divider = state.glyphs.divider.to_string().repeat(40);
+            let dividers: Vec<usize> = (0..frame.len()).filter(|index| frame[*index] == divider).collect();
+            let expected = match position {
+                HudPosition::Top => 1,
+                HudPosition::Bottom => frame.len() - 2,
+            };
+            assert_eq!(dividers, vec![expected]);
+
+            let blank = " ".repeat(40);
+            let play = (state.play_top()..state.play_bottom()).filter(|row| *row != state.player_row());
+            assert!(play.map(|row| row as usize).all(|row| frame[row] == blank));
+        }
+    }
+
+    #[test]
+    fn scanlines_dim_every_other_row() {
+        let frame = format!("one\r\ntwo{}\r\nthree\r\nfour", colors::RESET);
+        let rows: Vec<String> = render::scanlines(&frame).split("\r\n").map(String::from).collect();
+
+        assert_eq!(rows[0], "one");
+        assert_eq!(rows[1], format!("{}two{}{}{}", colors::DIM, colors::RESET, colors::DIM, colors::UNDIM));
+        assert_eq!(rows[2], "three");
+        assert_eq!(rows[3], format!("{}four{}", colors::DIM, colors::UNDIM));
+    }
+
+    #[test]
+    fn window_title_only_changes_with_score_or_wave() {
+        let mut title = render::WindowTitle::default();
+        assert_eq!(title.update(0, 1).as_deref(), Some("\x1b]0;CLI-nvaders | Score: 0 | Wave: 1\x07"));
+        assert_eq!(title.update(0, 1), None);
+        assert_eq!(title.update(30, 1).as_deref(), Some("\x1b]0;CLI-nvaders | Score: 30 | Wave: 1\x07"));
+        assert_eq!(title.update(30, 1), None);
+        assert_eq!(title.update(30, 2).as_deref(), Some("\x1b]0;CLI-nvaders | Score: 30 | Wave: 2\x07"));
+    }
+
+    #[test]
+    fn frame_rates_target_their_frame_times() {
+        assert_eq!(FrameRate::Uncapped.target(), time::Duration::ZERO);
+        assert_eq!(FrameRate::Capped(120).target(), time::Duration::from_nanos(8_333_333));
+
+        // Uncapped draws a frame every pass, without ever waiting on one.
+        let mut pacer = Pacer::new(Some(8), FrameRate::Uncapped);
+        for _ in 0..3 {
+            assert!(pacer.advance(time::Duration::from_micros(10)).1.is_some());
+        }
+        assert!(pacer.until_due() == time::Duration::ZERO);
+
+        let mut pacer = Pacer::new(Some(8), FrameRate::Capped(120));
+        assert_eq!(pacer.advance(time::Duration::from_millis(5)), (0, None));
+        assert_eq!(pacer.until_due(), time::Duration::from_nanos(3_333_333));
+    }
+
+    #[test]
+    fn frame_rates_past_a_byte_are_paced_and_reported() {
+        let mut args = Arguments { frame_rate: FrameRate::Capped(300), ..Arguments::default() };
+        assert_eq!(args.validate(), Ok(()));
+
+        let target = args.frame_rate.target();
+        assert_eq!(target, time::Duration::from_nanos(3_333_333));
+        assert_eq!(measure_fps(target), 300);
+
+        let mut pacer = Pacer::new(args.sim_rate, args.frame_rate);
+        let frames = (0..1000).filter(|_| pacer.advance(time::Duration::from_millis(1)).1.is_some()).count();
+        assert_eq!(frames, 300);
+
+        args.frame_rate = FrameRate::Capped(MAX_REPORTED_FPS + 1);
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn row_buffer_is_reused_between_frames() {
+        let mut rows = render::RowBuffer::default();
+        rows.fill('=', 120);
+        let (address, capacity) = (rows.line.as_ptr(), rows.line.capacity());
+        let tint = rows.tint.as_ptr();
+
+        for _ in 0..100 {
+            rows.fill('=', 120);
+            assert_eq!(rows.line, "=".repeat(120));
+            assert_eq!((rows.line.as_ptr(), rows.line.capacity(), rows.tint.as_ptr()), (address, capacity, tint));
+        }
+
+        // Narrower rows fit the buffer just the same.
+        rows.fill(' ', 40);
+        assert_eq!(rows.line.len(), 40);
+        assert_eq!((rows.line.as_ptr(), rows.tint.as_ptr()), (address, tint));
+
+        // So do the grid a frame is composed into, and the rows painted from it.
+        let mut state = test_state(20, 40);
+        state.args.no_color = false;
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+        let mut rows = render::RowBuffer::default();
+        let first = render::render("", &state, &mut rows);
+        let allocations = rows.allocations();
+        assert_eq!(render::render("", &state, &mut rows), first);
+        assert_eq!(rows.allocations(), allocations);
+
+        render::compose_grid(&state, &mut rows);
+        rows.paint(state.args.color_depth);
+        assert_eq!(rows.allocations(), allocations);
+    }
+
+    #[test]
+    fn scripted_ticks_advance_the_simulation() {
+        let mut state = test_state(20, 40);
+        run_script(&mut state, &[&[Action::MoveLeft], &[Action::MoveLeft]]);
+        run_idle(&mut state, 3);
+
+        assert_eq!(state.frame, 5);
+        assert_eq!(state.player.pos.col, (40 - state.player.width) / 2 - 2);
+    }
+
+    #[test]
+    fn captured_frames_fill_the_terminal() {
+        let state = test_state(20, 40);
+        let frame = capture(&state);
+
+        assert_eq!(frame.len(), 20);
+        assert!(frame.iter().all(|line| line.width() == 40));
+        assert!(frame[0].starts_with("Framerate: 0 | Score: 0"));
+    }
+
+    #[test]
+    fn right_pad_fills_short_lines() {
+        assert_eq!(render::right_pad(String::from("ab"), 5), "ab   ");
+        assert_eq!(render::right_pad(String::new(), 3), "   ");
+    }
+
+    #[test]
+    fn right_pad_leaves_exact_lines_alone() {
+        assert_eq!(render::right_pad(String::from("abc"), 3), "abc");
+    }
+
+    #[test]
+    fn right_pad_cuts_long_lines() {
+        assert_eq!(render::right_pad(String::from("abcdef"), 3), "abc");
+        assert_eq!(render::right_pad(String::from("abc"), 0), "");
+    }
+
+    #[test]
+    fn right_pad_never_splits_a_wide_glyph() {
+        // "漢" takes two cells, it can't straddle the edge so it's replaced by padding.
+        assert_eq!(render::right_pad(String::from("ab漢"), 3), "ab ");
+        assert_eq!(render::right_pad(String::from("ab漢"), 4), "ab漢");
+        assert_eq!(render::right_pad(String::from("漢"), 3), "漢 ");
+    }
+
+    #[test]
+    fn left_pad_indents() {
+        assert_eq!(render::left_pad(0, String::from("ab"), 10), "ab");
+        assert_eq!(render::left_pad(3, String::from("ab"), 10), "   ab");
+    }
+
+    #[test]
+    fn left_pad_past_the_row_is_blank() {
+        assert_eq!(render::left_pad(4, String::from("ab"), 4), "    ");
+        assert_eq!(render::left_pad(9, String::from("ab"), 4), "    ");
+    }
+
+    #[test]
+    fn replace_at_replaces_a_single_cell() {
+        let mut line = String::from("abc");
+        render::replace_at(&mut line, 'x', 1);
+        assert_eq!(line, "axc");
+    }
+
+    #[test]
+    fn replace_at_past_the_end_is_a_no_op() {
+        let mut line = String::from("abc");
+        render::replace_at(&mut line, 'x', 3);
+        assert_eq!(line, "abc");
+    }
+
+    #[test]
+    fn replace_at_keeps_the_width_around_wide_glyphs() {
+        // Narrow over wide pads the glyph's second cell.
+        let mut line = String::from("漢b");
+        render::replace_at(&mut line, 'x', 0);
+        assert_eq!(line, "x b");
+
+        // Either cell of a wide glyph replaces all of it.
+        let mut line = String::from("漢b");
+        render::replace_at(&mut line, 'x', 1);
+        assert_eq!(line, "x b");
+
+        // Wide over narrow swallows the following cell.
+        let mut line = String::from("abc");
+        render::replace_at(&mut line, '漢', 0);
+        assert_eq!(line, "漢c");
+        assert_eq!(line.width(), 3);
+    }
+
+    #[test]
+    fn difficulty_curve_ramps_up_by_its_formula() {
+        let curve = difficulty::DifficultyCurve { speed_growth: 0.2, fire_growth: 0.5, rows_every: 3, max_rows: 5 };
+        let first = curve.at(1, 12);
+        let tenth = curve.at(10, 12);
+
+        assert_eq!(first, difficulty::WaveParams { march_interval: 12, fire_interval: 12, rows: 3 });
+        // 12 / (1 + 0.2 * 9) = 4.29, 12 / (1 + 0.5 * 9) = 2.18, 3 + 9 / 3 rows capped at 5.
+        assert_eq!(tenth, difficulty::WaveParams { march_interval: 4, fire_interval: 2, rows: 5 });
+
+        for wave in 1..10 {
+            let (this, next) = (curve.at(wave, 12), curve.at(wave + 1, 12));
+            assert!(next.march_interval <= this.march_interval);
+            assert!(next.fire_interval <= this.fire_interval);
+            assert!(next.rows >= this.rows);
+        }
+    }
+
+    #[test]
+    fn waves_spawn_as_the_curve_says() {
+        let mut state = test_state(20, 40);
+        state.args.difficulty = difficulty::DifficultyCurve { rows_every: 1, ..difficulty::DifficultyCurve::default() };
+        state.enemies.clear();
+        state.spawn_wave();
+
+        assert_eq!(state.wave, 2);
+        assert_eq!(state.wave_params, state.args.difficulty.at(2, state.args.enemy_time));
+        assert_eq!(state.enemies.iter().map(|alien| alien.rank).max(), Some(3));
+    }
+
+    /** Fire a single shot straight up from under the given cell, as if the player had. */
+    fn shoot_at(state: &mut GameState, col: u16) {
+        let row = state.player_row() - 1;
+        state.projectiles.push(entities::Projectile { pos: entities::Pos::new(row, col), from_row: row, owner: entities::Owner::Player, drift: 0, y: row as f32, velocity: -1_f32 });
+    }
+
+    #[test]
+    fn split_aliens_leave_two_fragments_behind() {
+        let mut state = test_state(20, 40);
+        state.args.split_aliens = true;
+        state.enemies.clear();
+        state.spawn_wave();
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+
+        // Clear the way to a back-row alien at the right edge of the formation.
+        let target = state.enemies.iter().filter(|alien| alien.tier == 2).max_by_key(|alien| alien.pos.col).unwrap();
+        let (id, col) = (target.id, target.center());
+        state.enemies.retain(|alien| alien.tier == 2);
+        state.enemy_direction = 0;
+        shoot_at(&mut state, col);
+        let rows = state.size.rows as usize;
+        run_idle(&mut state, rows);
+
+        assert!(state.enemies.iter().all(|alien| alien.id != id));
+        let fragments: Vec<&entities::Alien> = state.enemies.iter().filter(|alien| alien.fragment).collect();
+        assert_eq!(fragments.len(), 2);
+        assert!(fragments.iter().all(|alien| alien.tier == 1 && alien.width == 1));
+        assert_eq!(fragments[1].pos.col, fragments[0].pos.col + 1);
+        assert!(fragments.iter().all(|alien| alien.pos.col < state.size.cols));
+    }
+
+    #[test]
+    fn kills_and_bonuses_are_worth_what_scoring_says() {
+        let mut state = test_state(20, 40);
+        state.args.scoring = scoring::ScoreConfig { alien_points: 7, ufo_points: 250, wave_bonus_per_life: 3 };
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+
+        // The back row isn't shielded, so a single hit on its right-most alien takes it down.
+        let back_row = state.enemies.iter().map(|alien| alien.pos.row).min().unwrap();
+        state.enemies.retain(|alien| alien.pos.row == back_row);
+        state.enemy_direction = 0;
+        let col = state.enemies.iter().map(|alien| alien.center()).max().unwrap();
+        shoot_at(&mut state, col);
+        let rows = state.size.rows as usize;
+        run_idle(&mut state, rows);
+        assert_eq!(state.score, 7);
+
+        // Clearing the rest of the wave along with the UFO, its bonus is awarded on the same frame.
+        state.enemies.clear();
+        let top = state.play_top();
+        state.ufo = Some(entities::Ufo { pos: entities::Pos::new(top, 10), from_col: 10, width: 3, direction: 1 });
+        state.projectiles.push(entities::Projectile { pos: entities::Pos::new(top + 1, 11), from_row: top + 1, owner: entities::Owner::Player, drift: 0, y: (top + 1) as f32, velocity: -1_f32 });
+        state.tick(&[]);
+        assert!(state.events.iter().any(|event| matches!(event, events::Event::UfoDestroyed { points: 250, .. })));
+        assert_eq!(state.score, 7 + 250 + 3 * state.lives as u32);
+    }
+
+    #[test]
+    fn fast_ufos_are_hit_anywhere_along_their_path() {
+        // Flying from columns 2-4 to 10-12 in a single step, with a shot rising into its row at `col`.
+        let fly = |col: u16| {
+            let mut state = test_state(20, 40);
+            state.args.ufo_speed = 8;
+            state.enemies.clear();
+            let top = state.play_top();
+            state.ufo = Some(entities::Ufo { pos: entities::Pos::new(top, 2), from_col: 2, width: 3, direction: 1 });
+            state.projectiles.push(entities::Projectile { pos: entities::Pos::new(top + 1, col), from_row: top + 1, owner: entities::Owner::Player, drift: 0, y: (top + 1) as f32, velocity: -1_f32 });
+            state.tick(&[]);
+            return state.events.iter().any(|event| matches!(event, events::Event::UfoDestroyed { .. }));
+        };
+
+        // It passes over a shot it'd otherwise have skipped right past, but not one it never got to.
+        assert!(fly(7));
+        assert!(fly(11));
+        assert!(!fly(20));
+    }
+
+    #[test]
+    fn sustained_slow_frames_engage_safe_mode() {
+        let args = Arguments { seed: Some(7), ..Arguments::default() };
+        let mut state = GameState::new(args.clone());
+        state.reset(args, Size { rows: 20, cols: 40 });
+        state.phase = Phase::Playing;
+        let frame = |state: &GameState| render::render("", state, &mut render::RowBuffer::default()).len();
+        let before = frame(&state);
+
+        // A fast frame breaks the streak.
+        let slow = time::Duration::from_millis(200);
+        for _ in 1..profile::SLOW_FRAME_STREAK {
+            assert!(!state.record_frame(slow));
+        }
+        assert!(!state.record_frame(time::Duration::from_millis(125)));
+        for _ in 1..profile::SLOW_FRAME_STREAK {
+            assert!(!state.record_frame(slow));
+        }
+        assert!(state.record_frame(slow));
+        assert!(!state.record_frame(slow), "only engages once");
+
+        assert!(state.args.safe_mode && state.args.no_color && state.args.ascii);
+        assert!(state.falling_stars.is_empty());
+        assert_eq!(state.args.frame_rate, FrameRate::Capped(SAFE_MODE_FRAME_RATE));
+        assert!(frame(&state) < before);
+    }
+
+    #[test]
+    fn shots_arc_back_down_under_gravity() {
+        let mut state = test_state(20, 40);
+        state.args.gravity = 0.25;
+        state.enemies.clear();
+        state.apply_action(Action::Fire);
+        let fired = state.projectiles[0].pos.row;
+
+        let mut rows = vec![fired];
+        let mut velocities = vec![state.projectiles[0].velocity];
+        while let Some(projectile) = state.projectiles.first() {
+            rows.push(projectile.pos.row);
+            velocities.push(projectile.velocity);
+            state.advance_projectiles(entities::Owner::Player, 1);
+            assert!(rows.len() < 100);
+        }
+
+        // Slowing down on its way up, until it falls back down past the player.
+        assert!(velocities.windows(2).all(|pair| pair[1] >= pair[0]));
+        let peak = rows.iter().copied().min().unwrap();
+        assert!(peak < fired && peak > state.play_top());
+        assert!(rows.last().copied().unwrap() > fired);
+        assert!(velocities.last().copied().unwrap() > 0_f32);
+    }
+
+    #[test]
+    fn shots_are_fired_from_the_sprites_origin() {
+        // From the tip of the ship, its middle cell, by default.
+        let mut state = test_state(20, 40);
+        assert_eq!((state.glyphs.player.as_str(), state.glyphs.player_origin), ("/^\\", 1));
+        state.player.pos.col = 5;
+        state.apply_action(Action::Fire);
+        assert_eq!(state.projectiles[0].pos.col, 6);
+
+        // Any cell of the sprite, but only those.
+        let fire = |state: &mut GameState| {
+            (state.projectiles, state.last_shot) = (vec![], None);
+            state.apply_action(Action::Fire);
+            return state.projectiles[0].pos.col;
+        };
+        state.glyphs.set("player_origin", "2").unwrap();
+        assert_eq!(fire(&mut state), 7);
+        assert!(state.glyphs.set("player_origin", "3").is_err(), "past a three-cell ship");
+        assert_eq!(state.glyphs.player_origin, 2, "left as it was");
+
+        // A wider ship has more cells to fire from, a narrower one needs its origin moved first.
+        state.glyphs.set("player", "<=^=>").unwrap();
+        state.glyphs.set("player_origin", "4").unwrap();
+        assert!(state.glyphs.set("player", "/^\\").is_err(), "origin 4 isn't a cell of a three-cell ship");
+        assert_eq!(state.glyphs.player, "<=^=>");
+        assert!(state.glyphs.set("player_origin", "5").is_err());
+        let overrides = |pairs: &[(&str, &str)]| pairs.iter().map(|(name, glyph)| (name.to_string(), glyph.to_string())).collect::<Vec<(String, String)>>();
+        let narrow = glyphs::GlyphMap::ascii().with_overrides(&overrides(&[("player", "A"), ("player_origin", "0")]));
+        assert_eq!(narrow.map(|glyphs| glyphs.player_origin), Ok(0), "given together, in either order");
+        let error = glyphs::GlyphMap::ascii().with_overrides(&overrides(&[("player", "A")])).unwrap_err();
+        assert_eq!(error, "Glyph 'player_origin' must be a cell of the 'player' sprite, 0 to 0, got '1'");
+
+        // Never from past the arena's edge.
+        state.player.width = 5;
+        state.player.pos.col = 37;
+        assert_eq!(fire(&mut state), 39);
+    }
+
+    #[test]
+    fn invasions_can_cost_a_life_rather_than_the_game() {
+        let mut state = test_state(20, 40);
+        state.args.invasion_costs_life = true;
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+
+        let (aliens, lives) = (state.enemies.len(), state.lives);
+        let row = state.invasion_row();
+        let invader = &mut state.enemies[0];
+        let id = invader.id;
+        (invader.pos.row, invader.y, invader.target_row) = (row, row as f32, row);
+        state.tick(&[]);
+
+        assert_eq!(state.lives, lives - 1);
+        assert!(state.events.contains(&events::Event::PlayerHit));
+        assert!(!state.events.contains(&events::Event::GameOver));
+        assert_eq!(state.enemies.len(), aliens - 1);
+        assert!(state.enemies.iter().all(|alien| alien.id != id));
+        assert_eq!(state.phase, Phase::Playing);
+    }
+
+    #[test]
+    fn environment_variables_sit_between_the_defaults_and_flags() {
+        let env = |vars: &[(&str, &str)]| vars.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect::<Vec<_>>();
+        let flags = |flags: &[&str]| flags.iter().map(|flag| flag.to_string()).collect::<Vec<_>>();
+        let vars = env(&[("CLI_NVADERS_FRAME_RATE", "12"), ("CLI_NVADERS_CRT", "1"), ("CLI_NVADERS_TICKER", "0"), ("HOME", "/root")]);
+
+        let args = Arguments::resolve(flags(&[]), vars.clone()).unwrap();
+        assert_eq!(args.frame_rate, FrameRate::Capped(12));
+        assert!(args.crt && !args.ticker);
+
+        let args = Arguments::resolve(flags(&["--frame-rate", "30", "--ticker"]), vars).unwrap();
+        assert_eq!(args.frame_rate, FrameRate::Capped(30));
+        assert!(args.crt && args.ticker);
+
+        assert!(Arguments::resolve(flags(&[]), env(&[("CLI_NVADERS_CRT", "maybe")])).unwrap_err().contains("CLI_NVADERS_CRT"));
+        assert!(Arguments::resolve(flags(&[]), env(&[("CLI_NVADERS_LIVE", "4")])).unwrap_err().contains("CLI_NVADERS_LIVE"));
+        let fps = Arguments::resolve(flags(&[]), env(&[("CLI_NVADERS_FPS", "24")])).unwrap();
+        assert_eq!(fps.frame_rate, FrameRate::Capped(24), "`fps` standing in for `frame_rate`");
+    }
+
+    #[test]
+    fn a_config_file_sits_below_the_environment_and_flags() {
+        let path = std::env::temp_dir().join(format!("clinvaders-config-{}", std::process::id()));
+        std::fs::write(&path, "# Settings for the living room\nfps = 20\n\nticker = true\nlives=5\n  hud = bottom  \n").unwrap();
+        let env = |vars: &[(&str, &str)]| vars.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect::<Vec<_>>();
+        let flags = |flags: &[&str]| flags.iter().map(|flag| flag.to_string()).collect::<Vec<_>>();
+        let config = path.display().to_string();
+        let vars = env(&[("CLI_NVADERS_FRAME_RATE", "12"), ("CLI_NVADERS_CRT", "1"), ("CLI_NVADERS_LIVES", "4")]);
+
+        // Each layer overriding the ones below: defaults, file, environment, flags.
+        let args = Arguments::resolve(flags(&["--config", &config, "--lives", "7"]), vars.clone()).unwrap();
+        assert_eq!(args.frame_rate, FrameRate::Capped(12), "the environment over the file");
+        assert!(args.crt, "the environment where the file says nothing");
+        assert!(args.ticker && args.hud_position == HudPosition::Bottom, "the file over the defaults");
+        assert_eq!(args.lives, 7, "flags over everything");
+        assert_eq!(args.config, Some(path.clone()));
+
+        // Named through the environment, just the same.
+        let mut named = vars.clone();
+        named.push((String::from("CLI_NVADERS_CONFIG"), config.clone()));
+        let args = Arguments::resolve(flags(&[]), named).unwrap();
+        assert_eq!((args.frame_rate, args.lives, args.ticker), (FrameRate::Capped(12), 4, true));
+
+        // Only the file over the defaults.
+        let args = Arguments::resolve(flags(&["--config", &config]), env(&[])).unwrap();
+        assert_eq!((args.frame_rate, args.lives), (FrameRate::Capped(20), 5));
+
+        // Without one, the environment over the defaults.
+        let args = Arguments::resolve(flags(&[]), vars).unwrap();
+        assert_eq!((args.frame_rate, args.lives, args.ticker), (FrameRate::Capped(12), 4, false));
+
+        std::fs::write(&path, "lives = 5\nticker\n").unwrap();
+        let error = Arguments::resolve(flags(&["--config", &config]), env(&[])).unwrap_err();
+        assert_eq!(error, format!("Invalid line 2 of '{config}', expected '<flag> = <value>'"));
+        std::fs::write(&path, "lives = many\n").unwrap();
+        assert!(Arguments::resolve(flags(&["--config", &config]), env(&[])).unwrap_err().contains(&format!("line 1 of '{config}'")));
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(Arguments::resolve(flags(&["--config", &config]), env(&[])).unwrap_err().starts_with("Failed to read the config file"));
+    }
+
+    #[test]
+    fn a_time_attack_ends_once_the_clock_runs_out() {
+        let mut state = test_state(20, 40);
+        (state.args.time_limit, state.args.sim_rate, state.args.invincible) = (Some(3), Some(10), true);
+        state.score = 120;
+
+        run_idle(&mut state, 20);
+        assert_eq!(state.time_left(), Some(10));
+        assert!(render::status_line(0, &state).contains("Time: 1s"));
+        run_idle(&mut state, 9);
+        assert_eq!(state.phase, Phase::Playing);
+
+        state.tick(&[]);
+        assert_eq!(state.phase, Phase::GameOver);
+        assert_eq!(state.score, 120);
+        assert!(state.lives > 0);
+    }
+
+    #[test]
+    fn bounds_cover_exactly_an_entitys_cells() {
+        let state = test_state(20, 40);
+        let alien = state.enemies.iter().find(|alien| alien.width > 1).unwrap();
+        let bounds = alien.bounds();
+        for row in 0..state.size.rows {
+            for col in 0..state.size.cols {
+                let sprite = row == alien.pos.row && (alien.pos.col..alien.pos.col + alien.width).contains(&col);
+                assert_eq!(bounds.contains(row, col), sprite, "{row},{col}");
+            }
+        }
+
+        let rect = |row, col, w, h| entities::Rect { row, col, w, h };
+        assert!(bounds.intersects(&rect(alien.pos.row, alien.pos.col + alien.width - 1, 1, 1)));
+        assert!(!bounds.intersects(&rect(alien.pos.row, alien.pos.col + alien.width, 1, 1)));
+        assert!(rect(0, 0, 3, 3).intersects(&rect(2, 2, 3, 3)) && !rect(0, 0, 3, 3).intersects(&rect(3, 0, 3, 3)));
+    }
+
+    #[test]
+    fn the_status_line_shows_the_selected_rates_unless_clean() {
+        let mut state = test_state(20, 40);
+        state.args.sim_rate = Some(30);
+        let status = |state: &GameState| {
+            let frame = render::render(&render::status_line(12, state), state, &mut render::RowBuffer::default());
+            return frame.split("\r\n").next().unwrap_or_default().trim_end().to_string();
+        };
+        assert!(status(&state).starts_with("Framerate: 12 | Score: 0"));
+
+        state.args.rate_display = RateDisplay::Sim;
+        assert!(status(&state).starts_with("Sim rate: 30 | Score: 0"));
+        state.args.rate_display = RateDisplay::Both;
+        assert!(status(&state).starts_with("Sim rate: 30 | Framerate: 12 | Score: 0"));
+
+        state.args.show_debug = false;
+        assert!(status(&state).starts_with("Score: 0"));
+        assert!(!status(&state).contains("rate"));
+    }
+
+    #[test]
+    fn the_theme_key_cycles_through_every_theme() {
+        let mut state = test_state(20, 40);
+        state.args.no_color = false;
+        let key = crossterm::event::KeyEvent::new(crossterm::event::KeyCode::Char('t'), crossterm::event::KeyModifiers::NONE);
+        let press = input::map_key(key, input::Keymap::Game).unwrap();
+
+        state.tick(&[press]);
+        assert_eq!(state.theme, 1);
+        state.tick(&[press]);
+        assert_eq!(state.theme, 2);
+        let ufo = state.theme().ufo;
+        assert_eq!(ufo, colors::THEMES[2].ufo);
+
+        for _ in 2..colors::THEMES.len() {
+            state.tick(&[press]);
+        }
+        assert_eq!(state.theme, 0, "wraps back around to the first");
+    }
+
+    #[test]
+    fn ultra_wide_terminals_only_redraw_the_arenas_columns() {
+        let args = Arguments { seed: Some(7), no_color: true, arena_growth: Some(u32::MAX), min_rows: 20, min_cols: 60, ..Arguments::default() };
+        let mut state = GameState::new(args.clone());
+        state.reset(args, Size { rows: 20, cols: 200 });
+        state.phase = Phase::Playing;
+        assert_eq!((state.size.rows, state.size.cols), (20, 60));
+
+        let mut rows = render::RowBuffer::default();
+        let widths = |frame: String| frame.split("\r\n").map(|line| line.chars().count()).collect::<Vec<usize>>();
+        let first = widths(render::render("", &state, &mut rows));
+        assert_eq!(first, vec![200; 20], "the margin is blanked on the first frame");
+
+        run_idle(&mut state, 5);
+        let next = widths(render::render("", &state, &mut rows));
+        assert_eq!(next.len(), 20);
+        assert!(next.iter().all(|width| *width == 60), "only the arena's columns after that");
+
+        // Any other background (or a resize) has the margin written out again.
+        state.args.background_char = '.';
+        assert_eq!(widths(render::render("", &state, &mut rows)), vec![200; 20]);
+        state.args.background_char = ' ';
+        assert_eq!(widths(render::render("", &state, &mut rows)), vec![200; 20]);
+        assert_eq!(widths(render::render("", &state, &mut rows)), vec![60; 20]);
+        state.terminal.cols = 180;
+        assert_eq!(widths(render::render("", &state, &mut rows)), vec![180; 20]);
+    }
+
+    #[test]
+    fn saved_games_continue_exactly_where_they_left_off() {
+        let mut state = test_state(20, 40);
+        run_script(&mut state, &[&[Action::MoveLeft, Action::Fire] as &[Action]; 40]);
+        run_idle(&mut state, 25);
+        assert!(state.in_progress() && !state.projectiles.is_empty());
+        let text = save::write(&state);
+
+        // Continued on the title screen of another session, seeded differently.
+        let mut restored = GameState::new(Arguments { seed: Some(99), no_color: true, ..Arguments::default() });
+        restored.reset(restored.args.clone(), Size { rows: 20, cols: 40 });
+        restored.phase = Phase::Title;
+        restored.saved = Some(text.clone());
+        restored.tick(&[Action::Continue]);
+        assert!(restored.is_paused());
+        assert!(restored.saved.is_none());
+        restored.phase = state.phase;
+
+        assert_eq!((restored.frame, restored.score, restored.lives, restored.wave), (state.frame, state.score, state.lives, state.wave));
+        assert_eq!(restored.player.pos, state.player.pos);
+        assert_eq!(restored.enemies.iter().map(|alien| (alien.id, alien.pos)).collect::<Vec<_>>(), state.enemies.iter().map(|alien| (alien.id, alien.pos)).collect::<Vec<_>>());
+        assert_eq!(restored.rng.get_seed(), state.rng.get_seed());
+        assert_eq!(save::write(&restored), text);
+
+        // Down to the RNG, both play out the same from there.
+        for _ in 0..200 {
+            state.tick(&[Action::Fire]);
+            restored.tick(&[Action::Fire]);
+        }
+        assert_eq!(save::write(&restored), save::write(&state));
+
+        // Another version's save is refused, and discarded when loaded.
+        let other = text.replacen(&format!("\"version\": {}", save::VERSION), "\"version\": 0", 1);
+        assert_ne!(other, text);
+        assert!(save::restore(&mut test_state(20, 40), &other).is_err());
+        let path = std::env::temp_dir().join(format!("clinvaders-save-{}", std::process::id()));
+        std::fs::write(&path, other).unwrap();
+        assert_eq!(save::load(&path).unwrap(), None);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn saves_that_dont_make_sense_are_discarded() {
+        let mut state = test_state(20, 40);
+        assert!(std::iter::repeat_n(&[Action::MoveRight, Action::Fire] as &[Action], 600).enumerate().all(|(frame, actions)| {
+            state.tick(actions);
+            return !state.in_progress() || frame % 10 != 0 || save::restore(&mut test_state(20, 40), &save::write(&state)).is_ok();
+        }));
+
+        let mut state = test_state(20, 40);
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+        let text = save::write(&state);
+        let edit = |change: &dyn Fn(&mut serde_json::Value)| {
+            let mut save: serde_json::Value = serde_json::from_str(&text).unwrap();
+            change(&mut save);
+            return save.to_string();
+        };
+
+        for (what, broken) in [
+            ("a countdown already over", edit(&|save| save["phase"] = serde_json::json!({ "Countdown": 0 }))),
+            ("a phase never saved", edit(&|save| save["phase"] = serde_json::json!("GameOver"))),
+            ("a destroyed alien", edit(&|save| save["enemies"][0]["hp"] = serde_json::json!(0))),
+            ("an alien past the edge", edit(&|save| save["enemies"][0]["pos"]["col"] = serde_json::json!(39))),
+            ("a star below the arena", edit(&|save| save["stars"] = serde_json::json!([{ "row": 20, "col": 3 }]))),
+            ("an arena too small to play in", edit(&|save| save["arena"] = serde_json::json!({ "rows": 4, "cols": 40 }))),
+        ] {
+            let mut restored = test_state(20, 40);
+            assert!(save::restore(&mut restored, &broken).is_err(), "{what}");
+            assert_eq!(restored.frame, 0, "{what} leaves the game alone");
+
+            let path = std::env::temp_dir().join(format!("clinvaders-broken-save-{}", std::process::id()));
+            std::fs::write(&path, broken).unwrap();
+            assert_eq!(save::load(&path).unwrap(), None, "{what}");
+            assert!(!path.exists(), "{what}");
+        }
+
+        // The last frame of a countdown is continued (and drawn) just fine.
+        let mut restored = test_state(20, 40);
+        save::restore(&mut restored, &edit(&|save| save["phase"] = serde_json::json!({ "Countdown": 1 }))).unwrap();
+        assert_eq!(restored.phase, Phase::Countdown(1));
+        assert!(capture(&restored).iter().any(|line| line.contains("GO!")));
+    }
+
+    #[test]
+    fn practice_respawns_aliens_and_never_costs_a_life() {
+        let mut state = test_state(20, 40);
+        state.args.practice = true;
+        state.args.respawn_delay = Some(5);
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+
+        let aliens = state.enemies.len();
+        let target = state.enemies.iter().filter(|alien| !alien.is_shielded()).max_by_key(|alien| alien.pos.row).unwrap();
+        let (id, pos) = (target.id, target.pos);
+        state.enemies.retain(|alien| alien.id == id);
+        state.enemy_direction = 0;
+        let col = state.enemies[0].center();
+        shoot_at(&mut state, col);
+        for _ in 0..state.size.rows {
+            if state.enemies.is_empty() {
+                break;
+            }
+            state.tick(&[]);
+        }
+
+        // Gone for the delay, then back where it was (as a new alien).
+        assert!(state.enemies.is_empty());
+        run_idle(&mut state, 4);
+        assert!(state.enemies.is_empty());
+        run_idle(&mut state, 1);
+        assert_eq!(state.enemies.len(), 1);
+        assert_eq!(state.enemies[0].pos, pos);
+        assert_ne!(state.enemies[0].id, id);
+        assert!(aliens > 1 && state.phase == Phase::Playing);
+
+        let lives = state.lives;
+        for _ in 0..10 {
+            state.player_hit();
+        }
+        assert_eq!(state.lives, lives);
+        assert_eq!(state.phase, Phase::Playing);
+        assert_eq!(state.args.leaderboard_path(), None);
+    }
+
+    #[test]
+    fn flooded_input_only_keeps_the_latest_actions() {
+        use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers};
+        let key = |code, kind| Event::Key(KeyEvent { code, modifiers: KeyModifiers::NONE, kind, state: KeyEventState::NONE });
+
+        let now = time::Instant::now();
+        let mut state = test_state(20, 40);
+        let mut queue = input::InputQueue::new(3);
+        let mut held = input::HeldKeys::new(true);
+        let mut step = |state: &mut GameState, queue: &mut input::InputQueue| {
+            let queued = queue.drain(&mut held);
+            let mut actions = held.movement(now);
+            actions.extend(queued);
+            state.tick(&input::resolve(&actions, input::InputMode::Queue));
+            return actions;
+        };
+
+        // A stall's worth of `a` presses (and repeats) is dropped in favour of the latest taps of `d`,
+        // which move the ship once as a tap does.
+        let col = state.player.pos.col;
+        for _ in 0..10 {
+            input::handle(key(KeyCode::Char('a'), KeyEventKind::Press), &mut queue, input::Keymap::Game, now);
+        }
+        for kind in [KeyEventKind::Press, KeyEventKind::Release].repeat(3) {
+            input::handle(key(KeyCode::Char('d'), kind), &mut queue, input::Keymap::Game, now);
+        }
+        assert_eq!(step(&mut state, &mut queue), [Action::MoveRight]);
+        assert_eq!(state.player.pos.col, col + 1);
+
+        // Only the latest actions are applied, while the release of a held key is kept however much follows it.
+        input::handle(key(KeyCode::Left, KeyEventKind::Press), &mut queue, input::Keymap::Game, now);
+        assert_eq!(step(&mut state, &mut queue), [Action::MoveLeft]);
+        input::handle(key(KeyCode::Left, KeyEventKind::Release), &mut queue, input::Keymap::Game, now);
+        for code in [KeyCode::Char('p'), KeyCode::Char('t'), KeyCode::Char('t'), KeyCode::Char('t')] {
+            input::handle(key(code, KeyEventKind::Press), &mut queue, input::Keymap::Game, now);
+        }
+        assert_eq!(step(&mut state, &mut queue), [Action::CycleTheme; 3]);
+        assert_eq!(state.player.pos.col, col);
+        assert!(!matches!(state.phase, Phase::Paused(_)));
+        assert!(step(&mut state, &mut queue).is_empty());
+    }
+
+    #[test]
+    fn a_lone_alien_never_marches_faster_than_the_floor() {
+        let mut state = test_state(20, 40);
+        let full = state.enemy_step_interval();
+        assert_eq!(full, state.wave_params.march_interval);
+
+        let wave_size = state.enemies.len();
+        state.enemies.truncate(wave_size / 2);
+        assert!(state.enemy_step_interval() < full);
+
+        for floor in [1, 3, 5] {
+            state.args.min_enemy_time = floor;
+            state.enemies.truncate(1);
+            assert_eq!(state.enemy_step_interval(), floor);
+        }
+    }
+
+    #[test]
+    fn a_finished_game_reports_its_outcome() {
+        let mut state = test_state(20, 40);
+        state.lives = 1;
+        run_script(&mut state, &[&[Action::Fire], &[], &[]]);
+
+        // Shot down from right above the ship.
+        let pos = entities::Pos::new(state.player_row() - 1, state.player.center());
+        state.projectiles.push(entities::Projectile { pos, from_row: pos.row, owner: entities::Owner::Enemy, drift: 0, y: pos.row as f32, velocity: 0_f32 });
+        for _ in 0..4 {
+            state.tick(&[]);
+        }
+
+        let outcome = state.outcome();
+        assert_eq!(outcome.result, GameResult::Lost);
+        assert_eq!(outcome.exit_code(), 0, "losing is a clean end of play");
+        assert_eq!((outcome.score, outcome.wave), (state.score, 1));
+        assert_eq!(outcome.stats.shots_fired, 1);
+        assert_eq!(outcome.stats.frames, state.frame);
+    }
+
+    #[test]
+    fn ticker_shows_the_latest_headlines_in_order() {
+        let mut state = test_state(20, 40);
+        state.args.ticker = true;
+        state.emit(events::Event::AlienDestroyed { id: 1, row: 3, col: 2, points: 10 });
+        state.emit(events::Event::ShotMissed);
+        state.emit(events::Event::PlayerHit);
+        state.emit(events::Event::UfoDestroyed { row: 2, col: 5, points: 150 });
+        state.emit(events::Event::ExtraLife);
+
+        assert_eq!(state.ticker, ["Life lost!", "UFO +150!", "Extra life!"]);
+        let frame = capture(&state);
+        assert_eq!(frame[1].trim_end(), "Life lost!  UFO +150!  Extra life!");
+        assert_eq!(frame[2], state.glyphs.divider.to_string().repeat(40));
+    }
+
+    #[test]
+    fn the_bundled_demo_plays_out_the_same_game() {
+        let demo = replay::Replay::demo().unwrap();
+        assert_eq!(replay::Replay::parse(&demo.to_string()), Ok(demo.clone()));
+
+        let args = Arguments { seed: Some(demo.seed), force_size: Some((demo.rows, demo.cols)), demo: true, ..Arguments::default() };
+        let mut state = GameState::new(args.clone());
+        state.reset(args, Size { rows: demo.rows, cols: demo.cols });
+        let mut playback = replay::Playback::new(demo);
+        while let Some(step) = playback.next_step() {
+            assert!(matches!(state.phase, Phase::Countdown(_) | Phase::Playing | Phase::WaveCleared(_)));
+            state.tick(&step);
+        }
+
+        assert_eq!(state.phase, Phase::GameOver);
+        assert_eq!((state.score, state.wave), (230, 1));
+    }
+
+    #[test]
+    fn an_idle_title_screen_hands_over_to_the_ai() {
+        let mut state = test_state(20, 40);
+        state.args.force_size = Some((20, 40));
+        state.phase = Phase::Title;
+        let delay = state.attract_delay() as usize;
+
+        // Any input starts the wait over.
+        run_idle(&mut state, delay - 1);
+        state.tick(&[Action::MoveLeft]);
+        run_idle(&mut state, delay - 1);
+        assert_eq!(state.phase, Phase::Title);
+        state.tick(&[]);
+        assert!(state.attract && state.args.demo);
+        assert!(matches!(state.phase, Phase::Countdown(_)));
+
+        // Only ever moving and firing, the AI shoots aliens down on its own until the game's over.
+        let mut kills = 0;
+        for _ in 0..20_000 {
+            let actions = ai::decide(&state);
+            assert!(actions.iter().all(|action| matches!(action, Action::MoveLeft | Action::MoveRight | Action::Fire)));
+            kills = kills.max(state.stats.aliens_killed);
+            state.tick(&actions);
+            if !state.attract {
+                break;
+            }
+        }
+        assert!(kills > 0);
+        assert_eq!(state.phase, Phase::Title);
+        assert!(!state.args.demo && state.score == 0);
+
+        // Somebody playing hands the title screen back straight away.
+        run_idle(&mut state, delay);
+        assert!(state.attract);
+        game_loop(&mut state, vec![Action::Fire]).unwrap();
+        assert!(!state.attract && !state.args.demo);
+        assert_eq!(state.phase, Phase::Title);
+    }
+
+    #[test]
+    fn gamepad_events_map_to_the_keyboards_actions() {
+        use gamepad::{PadButton, PadEvent};
+
+        let now = time::Instant::now();
+        let mut pad = gamepad::Gamepad::default();
+        let mut buffer = input::InputQueue::new(16);
+        for event in [PadEvent::Pressed(PadButton::South), PadEvent::Pressed(PadButton::Start), PadEvent::Pressed(PadButton::Up)] {
+            pad.handle(event, input::Keymap::Game, &mut buffer, now);
+        }
+        pad.handle(PadEvent::Pressed(PadButton::South), input::Keymap::Menu, &mut buffer, now);
+        let mut held = input::HeldKeys::new(true);
+        assert_eq!(buffer.drain(&mut held), [Action::Fire, Action::Pause, Action::Confirm]);
+
+        // Directions are held until let go, the d-pad's and the stick's alike.
+        pad.handle(PadEvent::Pressed(PadButton::Left), input::Keymap::Game, &mut buffer, now);
+        assert_eq!(pad.movement(now), [Action::MoveLeft]);
+        assert_eq!(pad.movement(now), [Action::MoveLeft]);
+        pad.handle(PadEvent::Released(PadButton::Left), input::Keymap::Game, &mut buffer, now);
+        assert!(pad.movement(now).is_empty());
+
+        pad.handle(PadEvent::StickX(0.1), input::Keymap::Game, &mut buffer, now);
+        assert!(pad.movement(now).is_empty());
+        pad.handle(PadEvent::StickX(0.9), input::Keymap::Game, &mut buffer, now);
+        assert_eq!(pad.movement(now), [Action::MoveRight]);
+        pad.handle(PadEvent::StickX(0.0), input::Keymap::Game, &mut buffer, now);
+        assert!(pad.movement(now).is_empty());
+        assert!(buffer.drain(&mut held).is_empty());
+    }
+
+    #[test]
+    fn composed_grids_hold_every_cells_glyph_and_color() {
+        let mut state = test_state(20, 40);
+        state.args.no_color = false;
+        state.glyphs = glyphs::GlyphMap::detect(true);
+        let alien = state.enemies.iter().find(|alien| !alien.is_shielded()).unwrap().clone();
+        state.enemies = vec![alien.clone()];
+        state.player.pos.col = 4;
+
+        let grid = state.compose_grid();
+        assert_eq!(grid.rows(), (state.play_bottom() - state.play_top()) as usize);
+        assert_eq!(grid.cell(0, 0), None, "the HUD isn't part of the grid");
+
+        let cell = grid.cell(alien.pos.row, alien.pos.col).unwrap();
+        assert_eq!(cell.glyph, state.glyphs.alien.chars().next().unwrap());
+        assert_eq!(cell.color, Some(colors::THEMES[0].alien_color(alien.pos.row, state.play_top(), state.player_row())));
+
+        let player = grid.cell(state.player_row(), 4).unwrap();
+        assert_eq!((player.glyph, player.color), (state.glyphs.player.chars().next().unwrap(), None));
+        assert_eq!(grid.cell(alien.pos.row + 1, alien.pos.col).map(|cell| (cell.glyph, cell.color)), Some((' ', None)));
+    }
+
+    #[test]
+    fn init_state_sets_up_a_game_on_the_title_screen() {
+        let args = Arguments { seed: Some(7), force_size: Some((20, 40)), ascii: true, safe_mode: true, ..Arguments::default() };
+        let state = init_state(args).unwrap();
+
+        assert_eq!(state.phase, Phase::Title);
+        assert_eq!((state.size.rows, state.size.cols), (20, 40));
+        assert_eq!((state.lives, state.score, state.wave), (state.args.starting_lives(), 0, 1));
+        assert!(!state.enemies.is_empty());
+        assert_eq!(state.player.pos, entities::Pos::new(state.player_row(), (40 - state.player.width) / 2));
+        assert_eq!(state.glyphs, glyphs::GlyphMap::ascii());
+        assert!(state.args.no_color && state.replay.is_none());
+
+        let demo = init_state(Arguments { demo: true, ..Arguments::default() }).unwrap();
+        assert!(demo.replay.is_some() && demo.phase != Phase::Title);
+    }
+
+    #[test]
+    fn run_loop_ends_once_asked_to_stop() {
+        // Without a terminal to read from, input errors are only reported.
+        let args = Arguments { seed: Some(7), force_size: Some((20, 40)), panic_on_errors: false, ..Arguments::default() };
+        let mut state = init_state(args).unwrap();
+        let stop = AtomicBool::new(false);
+
+        let outcome = std::thread::scope(|scope| {
+            scope.spawn(|| {
+                std::thread::sleep(time::Duration::from_millis(200));
+                stop.store(true, Ordering::Relaxed);
+            });
+
+            let renderer = render::Renderer::new(Vec::<u8>::new(), render::ScreenMode::Ansi);
+            return run_loop(&mut state, renderer, false, &stop);
+        });
+
+        assert_eq!(outcome.unwrap().result, GameResult::Quit);
+        assert_eq!(state.phase, Phase::Title);
+    }
+
+    /** Fails every write with the given kind of error, like stdout would piped into a closed pipe. */
+    struct FailingWriter(io::ErrorKind);
+
+    impl io::Write for FailingWriter {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            return Err(io::Error::from(self.0));
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            return Err(io::Error::from(self.0));
+        }
+    }
+
+    #[test]
+    fn a_closed_pipe_shuts_the_renderer_down_rather_than_failing() {
+        let mut renderer = render::Renderer::new(FailingWriter(io::ErrorKind::BrokenPipe), render::ScreenMode::Ansi);
+        assert_eq!(renderer.present("frame").unwrap(), render::Presented::Closed);
+
+        // Anything else is still an error.
+        let mut renderer = render::Renderer::new(FailingWriter(io::ErrorKind::PermissionDenied), render::ScreenMode::Ansi);
+        assert_eq!(renderer.present("frame").unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+    }
+
+    /** Reports a fixed size, standing in for the terminal. */
+    #[derive(Debug)]
+    struct MockSize(u16, u16);
+
+    impl viewport::SizeProvider for MockSize {
+        fn size(&self) -> Option<Size> {
+            return Some(Size { rows: self.0, cols: self.1 });
+        }
+    }
+
+    #[test]
+    fn evaluate_state_asks_the_size_provider() {
+        let mut state = test_state(20, 40);
+        state.sizes = Box::new(MockSize(24, 60));
+        state.evaluate_state().unwrap();
+
+        assert_eq!((state.terminal.rows, state.terminal.cols), (24, 60));
+        assert_eq!((state.size.rows, state.size.cols), (24, 60));
+    }
+
+    #[test]
+    fn forced_sizes_win_over_the_size_provider() {
+        let mut state = test_state(20, 40);
+        state.sizes = Box::new(MockSize(24, 60));
+        state.args.force_size = Some((30, 50));
+        state.evaluate_state().unwrap();
+
+        assert_eq!((state.terminal.rows, state.terminal.cols), (30, 50));
+        assert_eq!((state.size.rows, state.size.cols), (30, 50));
+    }
+
+    #[test]
+    fn stars_stay_within_a_shrinking_terminal() {
+        let mut state = test_state(24, 80);
+        state.args.star_density = 200_f32;
+        state.sizes = Box::new(MockSize(24, 80));
+        state.evaluate_state().unwrap();
+        state.seed_stars();
+        assert!(state.falling_stars.iter().any(|star| star.pos.col >= 40));
+
+        state.sizes = Box::new(MockSize(24, 40));
+        state.evaluate_state().unwrap();
+        assert!(!state.falling_stars.is_empty());
+        assert!(state.falling_stars.iter().all(|star| star.pos.col < 40));
+
+        // Nor do they wander out drifting down the (narrower) rows.
+        run_idle(&mut state, 30);
+        assert!(state.falling_stars.iter().all(|star| star.pos.col < 40 && star.pos.row < state.play_bottom()));
+        capture(&state);
+    }
+
+    #[test]
+    fn the_simulation_steps_at_its_own_rate_between_frames() {
+        // At 4 steps and 8 frames a second, every other frame is drawn without the simulation having moved on.
+        let eighth = time::Duration::from_millis(125);
+        let mut pacer = Pacer::new(Some(4), FrameRate::Capped(8));
+        for _ in 0..10 {
+            let (first, second) = (pacer.advance(eighth), pacer.advance(eighth));
+            assert_eq!(first.0 + second.0, 1, "a single simulation step");
+            assert!(first.1.is_some() && second.1.is_some(), "two renders");
+        }
+    }
+
+    #[test]
+    fn the_pacer_never_drifts_over_long_sessions() {
+        // 7ms doesn't divide either rate's frame time, which an accumulated float would round off, frame after frame.
+        let mut pacer = Pacer::new(Some(60), FrameRate::Capped(120));
+        let (mut steps, mut frames) = (0_u64, 0_u64);
+        for _ in 0..100_000 {
+            let (due, frame) = pacer.advance(time::Duration::from_millis(7));
+            steps += due as u64;
+            frames += frame.is_some() as u64;
+        }
+
+        // 700 seconds in, exactly.
+        assert_eq!(steps, 700 * 60);
+        assert_eq!(frames, 700 * 120);
+    }
+
+    #[test]
+    fn near_zero_frame_times_report_a_clamped_framerate() {
+        assert_eq!(measure_fps(time::Duration::ZERO), MAX_REPORTED_FPS);
+        assert_eq!(measure_fps(time::Duration::from_nanos(1)), MAX_REPORTED_FPS);
+        assert_eq!(measure_fps(time::Duration::from_micros(900)), MAX_REPORTED_FPS);
+        assert_eq!(measure_fps(time::Duration::from_nanos(16_666_667)), 60);
+        assert_eq!(measure_fps(time::Duration::from_secs(1)), 1);
+
+        // Uncapped, a frame is drawn every pass however little time went by.
+        let mut pacer = Pacer::new(None, FrameRate::Uncapped);
+        let (_, frame_time) = pacer.advance(time::Duration::from_nanos(1));
+        assert_eq!(frame_time.map(measure_fps), Some(MAX_REPORTED_FPS));
+    }
+
+    #[test]
+    fn the_hud_can_sit_at_the_top_or_the_bottom() {
+        for (position, status, ship) in [(HudPosition::Top, 0, 19), (HudPosition::Bottom, 19, 17)] {
+            let mut args = test_state(20, 40).args;
+            (args.hud_position, args.stars) = (position, false);
+            let mut state = GameState::new(args.clone());
+            state.reset(args, Size { rows: 20, cols: 40 });
+            state.phase = Phase::Playing;
+            let frame = capture(&state);
+
+            assert!(frame[status].starts_with("Framerate: 0 | Score: 0"));
+            assert_eq!(state.player_row() as usize, ship);
+            assert_eq!(frame[ship].trim(), state.glyphs.player);
+
+            // Nothing of the play area's drawn over the HUD, nor the other way around.
+            let hud = (0..20).filter(|row| !(state.play_top()..state.play_bottom()).contains(row));
+            assert_eq!(hud.count(), 2);
+            let top = state.enemies.iter().map(|alien| alien.pos.row).min().unwrap();
+            assert!(top >= state.play_top() && frame[top as usize].contains(&state.glyphs.shielded));
+        }
+    }
+
+    #[test]
+    fn the_ascii_glyph_set_is_printable_ascii_only() {
+        let glyphs = glyphs::GlyphMap::ascii();
+        let singles = [glyphs.fragment, glyphs.projectile, glyphs.enemy_shot, glyphs.star, glyphs.barrier, glyphs.divider, glyphs.vignette];
+        let sprites = [&glyphs.player, &glyphs.alien, &glyphs.shielded, &glyphs.ufo];
+        let cells = singles.into_iter().chain(sprites.into_iter().flat_map(|sprite| sprite.chars()));
+        for glyph in cells {
+            assert!(('\x20'..='\x7e').contains(&glyph), "{glyph:?}");
+        }
+
+        // Forced with `--ascii`, whatever the terminal could otherwise render.
+        assert_eq!(glyphs::GlyphMap::detect(true), glyphs);
+        let frame = capture(&test_state(20, 40));
+        assert!(frame.iter().all(|line| line.chars().all(|glyph| (' '..='~').contains(&glyph))));
+    }
+
+    #[test]
+    fn a_shot_at_an_aliens_left_edge_destroys_all_of_it() {
+        let mut state = test_state(20, 40);
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+        let alien = state.enemies.iter().find(|alien| !alien.is_shielded() && alien.width == 3).unwrap().clone();
+        state.enemies = vec![alien.clone()];
+        state.enemy_direction = 0;
+        shoot_at(&mut state, alien.pos.col);
+        let rows = state.size.rows as usize;
+        run_idle(&mut state, rows);
+
+        assert!(state.enemies.is_empty());
+        assert_eq!(state.stats.aliens_killed, 1);
+        let grid = state.compose_grid();
+        assert!((0..3).all(|cell| grid.cell(alien.pos.row, alien.pos.col + cell).is_some_and(|cell| cell.glyph != 'W')));
+    }
+
+    #[test]
+    fn overridden_glyphs_are_what_the_renderer_draws() {
+        let mut state = test_state(20, 40);
+        let overrides = [(String::from("alien"), String::from("%M%")), (String::from("shielded"), String::from("{M}"))];
+        state.glyphs = glyphs::GlyphMap::ascii().with_overrides(&overrides).unwrap();
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+        let frame = capture(&state).join("\n");
+        assert!(frame.contains("%M%") && frame.contains("{M}"));
+        assert!(!frame.contains("/W\\") && !frame.contains("[W]"));
+
+        // Glyphs are validated, bar sprites a single printable character each.
+        let mut glyphs = glyphs::GlyphMap::ascii();
+        assert!(glyphs.set("projectile", "||").is_err());
+        assert!(glyphs.set("star", "\x07").is_err());
+        assert!(glyphs.set("alien", "ABCDEF").is_err());
+        assert!(glyphs.set("barrier", "").is_err());
+        assert!(glyphs.set("planet", "o").is_err());
+    }
+
+    #[test]
+    fn a_tiny_benchmark_reports_the_same_stats_every_run() {
+        let args = Arguments { seed: Some(7), ..Arguments::default() };
+        let (report, outcome) = benchmark::run(args.clone(), 100);
+        let (again, _) = benchmark::run(args, 100);
+
+        assert_eq!(report.frames, 100);
+        assert!(report.kills <= report.shots_fired && report.shots_fired > 0);
+        assert!(report.hits >= report.kills, "shielded aliens take hits without dying");
+        assert_eq!(report.accuracy(), outcome.stats.accuracy(), "the same accuracy as the session's");
+        assert_eq!((report.score, report.wave), (outcome.score, 1));
+        assert_eq!(outcome.exit_code(), 0);
+        let deterministic = |report: &benchmark::BenchmarkStats| (report.frames, report.kills, report.hits, report.shots_fired, report.score, report.wave);
+        assert_eq!(deterministic(&again), deterministic(&report));
+
+        let printed = report.to_string();
+        assert!(printed.starts_with("Frames:      100\n"));
+        assert!(printed.contains(&format!("Shots fired: {}\n", report.shots_fired)));
+    }
+
+    #[test]
+    fn a_reset_leaves_nothing_of_the_previous_game_behind() {
+        let mut state = test_state(20, 40);
+        state.args.ticker = true;
+        while state.phase == Phase::Playing {
+            let actions = ai::decide(&state);
+            state.tick(&actions);
+            assert!(state.frame < 100_000);
+        }
+        assert!(state.frame > 0 && state.stats.shots_fired > 0);
+
+        let mut fresh = GameState::new(state.args.clone());
+        fresh.reset(state.args.clone(), Size { rows: 20, cols: 40 });
+        state.reset(state.args.clone(), Size { rows: 20, cols: 40 });
+
+        // Everything a game plays out from (see `save::write`), and then some.
+        assert_eq!(save::write(&state), save::write(&fresh));
+        assert_eq!(state.phase, fresh.phase);
+        assert_eq!(state.stats, fresh.stats);
+        assert!(state.events.is_empty() && state.ticker.is_empty() && state.popups.is_empty() && state.initials.is_none());
+        let stars = |state: &GameState| state.falling_stars.iter().map(|star| star.pos).collect::<Vec<_>>();
+        assert_eq!(stars(&state), stars(&fresh));
+    }
+
+    #[test]
+    fn bullet_time_slows_everything_but_the_players_shots() {
+        let mut state = test_state(20, 40);
+        state.args.sim_rate = Some(10);
+        let (fire, shots, march) = (state.enemy_fire_interval(), state.cadence(ENEMY_SHOT_INTERVAL), state.cadence(state.enemy_step_interval()));
+        state.award(SLOWDOWN_SCORE_EVERY);
+        assert_eq!(state.slowdown, 50);
+        assert!(render::status_line(0, &state).contains("BULLET TIME 5s"));
+
+        assert_eq!(state.enemy_fire_interval(), fire * SLOWDOWN_FACTOR);
+        assert_eq!(state.cadence(ENEMY_SHOT_INTERVAL), shots * SLOWDOWN_FACTOR);
+        assert_eq!(state.cadence(state.enemy_step_interval()), march * SLOWDOWN_FACTOR);
+
+        // The player's shots climb as fast as ever.
+        state.apply_action(Action::Fire);
+        let row = state.projectiles[0].pos.row;
+        state.tick(&[]);
+        assert_eq!(state.projectiles[0].pos.row, row - state.args.projectile_speed as u16);
+
+        run_idle(&mut state, 49);
+        assert_eq!(state.slowdown, 0);
+        assert_eq!(state.enemy_fire_interval(), fire);
+        assert_eq!(state.cadence(ENEMY_SHOT_INTERVAL), shots);
+        assert!(!render::status_line(0, &state).contains("BULLET TIME"));
+    }
+
+    #[test]
+    fn session_stats_tally_a_scripted_run() {
+        let mut state = test_state(20, 40);
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+        let alien = state.enemies.iter().filter(|alien| !alien.is_shielded()).max_by_key(|alien| alien.pos.col).unwrap().clone();
+        state.enemies = vec![alien.clone()];
+        state.enemy_direction = 0;
+        let rows = state.size.rows as usize;
+
+        // A miss well clear of the alien, then a hit right under it.
+        state.player.pos.col = 0;
+        state.tick(&[Action::Fire]);
+        run_idle(&mut state, rows);
+        state.player.pos.col = alien.center() - state.glyphs.player_origin;
+        state.tick(&[Action::Fire]);
+        run_idle(&mut state, rows);
+
+        let stats = &state.stats;
+        assert_eq!((stats.shots_fired, stats.hits, stats.aliens_killed, stats.max_combo), (2, 1, 1, 1));
+        assert_eq!(stats.accuracy(), 50_f32);
+        assert_eq!(stats.frames, state.frame);
+        assert!(stats.summary(10).contains(&String::from("Hits: 1 (50.0%)")));
+    }
+
+    #[test]
+    fn clearing_every_wave_of_a_campaign_wins_it() {
+        let mut state = test_state(20, 80);
+        (state.args.total_waves, state.args.sim_rate) = (Some(2), Some(10));
+        assert!(render::status_line(0, &state).contains("Wave: 1/2"));
+
+        state.enemies.clear();
+        state.tick(&[]);
+        assert!(matches!(state.phase, Phase::WaveCleared(_)));
+        assert!(!state.events.contains(&events::Event::CampaignWon));
+        let interlude = state.interlude_duration() as usize;
+        run_idle(&mut state, interlude);
+        assert_eq!((state.phase, state.wave), (Phase::Playing, 2));
+        assert!(render::status_line(0, &state).contains("Wave: 2/2"));
+
+        state.enemies.clear();
+        state.tick(&[]);
+        assert!(state.events.contains(&events::Event::CampaignWon));
+        assert_eq!(state.phase, Phase::Won);
+        assert_eq!(state.outcome().result, GameResult::Won);
+        assert!(capture(&state).iter().any(|line| line.contains("YOU WON THE CAMPAIGN")));
+    }
+
+    #[test]
+    fn a_wide_ship_stays_on_screen_at_either_edge() {
+        let mut state = test_state(20, 40);
+        assert_eq!(state.player.width, 3);
+        let on_screen = |state: &GameState| (0..state.size.cols + 3).filter(|col| state.player.occupies(*col)).all(|col| col < state.size.cols);
+
+        run_script(&mut state, &[&[Action::MoveRight] as &[Action]; 40]);
+        assert_eq!(state.player.pos.col, 37);
+        assert!(on_screen(&state) && state.player.occupies(39));
+        assert!(capture(&state).iter().all(|line| line.width() == 40));
+
+        run_script(&mut state, &[&[Action::MoveLeft] as &[Action]; 40]);
+        assert_eq!(state.player.pos.col, 0);
+        assert!(on_screen(&state) && state.player.occupies(0));
+    }
+
+    #[test]
+    fn biased_fire_comes_from_the_players_column() {
+        let mut state = test_state(20, 40);
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+        let alien = state.enemies.iter().max_by_key(|alien| (alien.pos.row, alien.pos.col)).unwrap().clone();
+        state.player.pos.col = alien.center() - 1;
+        let shots = |state: &mut GameState| {
+            let mut cols = vec![];
+            for _ in 0..50 {
+                state.projectiles.clear();
+                state.enemy_fire();
+                cols.push(state.projectiles[0].pos.col);
+            }
+            return cols;
+        };
+
+        state.args.fire_bias = 1000_f32;
+        assert!(shots(&mut state).iter().all(|col| *col == alien.center()));
+
+        // Without a bias, any front-row alien may fire.
+        state.args.fire_bias = 0_f32;
+        assert!(shots(&mut state).iter().any(|col| *col != alien.center()));
+    }
+
+    #[test]
+    fn movement_clamps_or_wraps_at_the_edges() {
+        let mut state = test_state(20, 40);
+        state.player.pos.col = 37;
+        state.tick(&[Action::MoveRight]);
+        assert_eq!(state.player.pos.col, 37, "clamped at the right edge");
+        state.player.pos.col = 0;
+        state.tick(&[Action::MoveLeft]);
+        assert_eq!(state.player.pos.col, 0, "clamped at the left edge");
+
+        // Wrapping, the whole ship reappears on the other side.
+        state.args.movement_mode = MovementMode::Wrap;
+        state.tick(&[Action::MoveLeft]);
+        assert_eq!(state.player.pos.col, 37);
+        state.tick(&[Action::MoveRight]);
+        assert_eq!(state.player.pos.col, 0);
+    }
+
+    #[test]
+    fn aliens_redden_as_they_near_the_player() {
+        let theme = colors::THEMES[0];
+        assert_eq!(theme.alien_color(2, 2, 19), colors::ALIEN_SAFE);
+        assert_eq!(theme.alien_color(19, 2, 19), colors::ALIEN_DANGER);
+
+        // Ever redder (and less green) on the way down.
+        let near_top = theme.alien_color(4, 2, 19);
+        let near_bottom = theme.alien_color(17, 2, 19);
+        assert_ne!(near_top, near_bottom);
+        assert!(near_bottom.r > near_top.r && near_bottom.g < near_top.g);
+    }
+
+    #[test]
+    fn wide_glyphs_are_padded_by_their_width() {
+        // Two cells for "🚀", so one less space of padding than its char count would have it.
+        let padded = render::right_pad(String::from("a🚀"), 6);
+        assert_eq!(padded, "a🚀   ");
+        assert_eq!(padded.width(), 6);
+        assert_eq!(render::left_pad(2, String::from("🚀"), 6).width(), 4);
+
+        // A ship themed with one still keeps every row exactly as wide as the terminal.
+        let mut state = test_state(20, 40);
+        state.glyphs.set("player", "🚀").unwrap();
+        state.reset(state.args.clone(), Size { rows: 20, cols: 40 });
+        state.phase = Phase::Playing;
+        assert_eq!(state.player.width, 2);
+        run_script(&mut state, &[&[Action::MoveRight] as &[Action]; 40]);
+        assert_eq!(state.player.pos.col, 38);
+        assert!(capture(&state).iter().all(|line| line.width() == 40));
+    }
+
+    #[test]
+    fn opposite_taps_within_a_frame_resolve_by_the_input_mode() {
+        let taps = [Action::MoveLeft, Action::Fire, Action::MoveRight];
+        assert_eq!(input::resolve(&taps, input::InputMode::Queue), taps);
+        assert_eq!(input::resolve(&taps, input::InputMode::LastWins), [Action::MoveRight, Action::Fire]);
+        assert_eq!(input::resolve(&taps, input::InputMode::Cancel), [Action::Fire]);
+        assert_eq!(input::resolve(&[Action::MoveLeft, Action::MoveLeft, Action::MoveRight], input::InputMode::Cancel), [Action::MoveLeft]);
+
+        // Played out: back where it started, a column right, or not moved at all.
+        for (mode, offset) in [(input::InputMode::Queue, 0), (input::InputMode::LastWins, 1), (input::InputMode::Cancel, 0)] {
+            let mut state = test_state(20, 40);
+            let col = state.player.pos.col;
+            state.tick(&input::resolve(&[Action::MoveLeft, Action::MoveRight], mode));
+            assert_eq!(state.player.pos.col, col + offset, "{mode:?}");
+        }
+    }
+
+    #[test]
+    fn terminals_under_the_minimum_size_wait_to_be_resized() {
+        let mut state = test_state(20, 40);
+        (state.args.min_rows, state.args.min_cols) = (24, 60);
+        let playable = |state: &mut GameState, rows, cols| {
+            state.sizes = Box::new(MockSize(rows, cols));
+            state.evaluate_state().unwrap();
+            return state.is_playable();
+        };
+
+        assert!(!playable(&mut state, 23, 60));
+        assert!(!playable(&mut state, 24, 59));
+        assert_eq!(capture(&state)[..3], ["Terminal too small!", "Current: 59x24", "Required: 60x24"].map(|line| render::right_pad(String::from(line), 59)));
+        assert!(playable(&mut state, 24, 60));
+        assert!(playable(&mut state, 25, 61));
+    }
+
+    #[test]
+    fn aliens_drop_into_formation_before_becoming_active() {
+        let mut state = test_state(20, 40);
+        let top = state.play_top();
+        let targets: Vec<entities::Pos> = state.enemies.iter().map(|alien| entities::Pos::new(alien.target_row, alien.pos.col)).collect();
+        assert!(state.enemies.iter().all(|alien| alien.pos.row == top));
+        assert!(targets.iter().any(|target| target.row > top));
+
+        // Neither marching nor firing on the way down.
+        let mut rows: Vec<u16> = state.enemies.iter().map(|alien| alien.pos.row).collect();
+        for _ in 1..ENTRY_FRAMES {
+            state.tick(&[]);
+            assert!(matches!(state.formation, FormationPhase::EnteringFormation { .. }));
+            let next: Vec<u16> = state.enemies.iter().map(|alien| alien.pos.row).collect();
+            assert!(next.iter().zip(&rows).all(|(next, row)| next >= row));
+            rows = next;
+        }
+        assert!(state.projectiles.is_empty());
+
+        state.tick(&[]);
+        assert_eq!(state.formation, FormationPhase::Active);
+        assert_eq!(state.enemies.iter().map(|alien| alien.pos).collect::<Vec<_>>(), targets);
+    }
+
+    #[test]
+    fn aliens_fire_on_a_fixed_frame_schedule() {
+        // Frames enemy shots were fired on, along with where from.
+        let fired = |state: &mut GameState| {
+            let mut fired = vec![];
+            for _ in 0..120 {
+                let before = state.projectiles.iter().filter(|projectile| projectile.owner == entities::Owner::Enemy).count();
+                state.tick(&[]);
+                let after = state.projectiles.iter().filter(|projectile| projectile.owner == entities::Owner::Enemy).count();
+                if after > before {
+                    let shot = state.projectiles.iter().rfind(|projectile| projectile.owner == entities::Owner::Enemy).unwrap();
+                    fired.push((state.frame, shot.pos.col));
+                }
+            }
+            return fired;
+        };
+
+        let mut state = test_state(20, 40);
+        state.args.invincible = true;
+        let interval = state.enemy_fire_interval();
+        let shots = fired(&mut state);
+        let expected: Vec<u64> = (ENTRY_FRAMES as u64..=120).filter(|frame| frame % interval == 0).collect();
+        assert_eq!(shots.iter().map(|(frame, _)| *frame).collect::<Vec<u64>>(), expected);
+
+        // The same seed has the same aliens firing.
+        let mut again = test_state(20, 40);
+        again.args.invincible = true;
+        assert_eq!(fired(&mut again), shots);
+    }
+
+    #[test]
+    fn fast_shots_hit_aliens_on_rows_they_skip() {
+        let mut state = test_state(20, 40);
+        state.args.projectile_speed = 3;
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+        let mut alien = state.enemies.iter().find(|alien| !alien.is_shielded()).unwrap().clone();
+        (alien.pos.row, alien.y, alien.target_row) = (10, 10_f32, 10);
+        state.enemies = vec![alien.clone()];
+        state.enemy_direction = 0;
+
+        // Fired from row 18, the shot lands on rows 15, 12 and 9, never on 10.
+        state.player.pos.col = alien.center() - state.glyphs.player_origin;
+        let shot = |state: &GameState| state.projectiles.iter().find(|projectile| projectile.owner == entities::Owner::Player).map(|projectile| projectile.pos.row);
+        state.tick(&[Action::Fire]);
+        let mut rows = vec![];
+        while let Some(row) = shot(&state) {
+            rows.push(row);
+            state.tick(&[]);
+        }
+        assert_eq!(rows, [15, 12]);
+        assert!(state.events.iter().any(|event| matches!(event, events::Event::AlienDestroyed { id, .. } if *id == alien.id)));
+    }
+
+    #[test]
+    fn a_shot_jumping_over_an_alien_still_destroys_it() {
+        let mut state = test_state(20, 40);
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+        let alien = state.enemies.iter().find(|alien| !alien.is_shielded()).unwrap().clone();
+        state.enemies = vec![alien.clone()];
+
+        // Ending up two rows past the alien, from three rows short of it.
+        let (from, to) = (alien.pos.row + 3, alien.pos.row - 2);
+        let shot = entities::Projectile { pos: entities::Pos::new(to, alien.pos.col + 1), from_row: from, owner: entities::Owner::Player, drift: 0, y: to as f32, velocity: -5_f32 };
+        assert!(!alien.occupies(shot.pos.row, shot.pos.col) && shot.path().contains(&alien.pos.row));
+        state.projectiles = vec![shot];
+        state.resolve_collisions();
+
+        assert!(state.enemies.is_empty() && state.projectiles.is_empty());
+        assert!(state.events.iter().any(|event| matches!(event, events::Event::AlienDestroyed { id, .. } if *id == alien.id)));
+    }
+
+    #[test]
+    fn collisions_resolve_before_the_invasion_is_evaluated() {
+        let mut state = test_state(20, 40);
+        state.args.invasion_row_offset = 3;
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+        let row = state.invasion_row();
+        let mut invader = state.enemies.iter().find(|alien| !alien.is_shielded()).unwrap().clone();
+        (invader.pos.row, invader.y, invader.target_row) = (row, row as f32, row);
+        let bystander = state.enemies.iter().find(|alien| alien.id != invader.id && alien.pos.col != invader.pos.col).unwrap().clone();
+        state.enemies = vec![invader.clone(), bystander];
+        state.enemy_direction = 0;
+
+        // Shot down on the very frame it invades, the invader never got to.
+        state.projectiles.push(entities::Projectile { pos: entities::Pos::new(row + 1, invader.pos.col), from_row: row + 1, owner: entities::Owner::Player, drift: 0, y: (row + 1) as f32, velocity: -1_f32 });
+        state.tick(&[]);
+        assert!(state.events.iter().any(|event| matches!(event, events::Event::AlienDestroyed { id, .. } if *id == invader.id)));
+        assert!(!state.events.contains(&events::Event::GameOver));
+        assert_eq!(state.phase, Phase::Playing);
+
+        // Whereas the player going down on the frame the last alien does still loses the game.
+        let mut state = test_state(20, 40);
+        state.lives = 1;
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+        let last = state.enemies.iter().find(|alien| !alien.is_shielded()).unwrap().clone();
+        state.enemies = vec![last.clone()];
+        state.enemy_direction = 0;
+        state.projectiles.clear();
+        shoot_at(&mut state, last.pos.col);
+        state.projectiles[0].pos.row = last.pos.row + 1;
+        let pos = entities::Pos::new(state.player_row() - 1, state.player.center());
+        state.projectiles.push(entities::Projectile { pos, from_row: pos.row, owner: entities::Owner::Enemy, drift: 0, y: pos.row as f32, velocity: 0_f32 });
+        state.frame = 1; // Enemy shots only move every other frame, this next one being one of them.
+        state.tick(&[]);
+        assert!(state.enemies.is_empty());
+        assert!(state.events.contains(&events::Event::GameOver));
+        assert_eq!(state.phase, Phase::GameOver);
+    }
+
+    #[test]
+    fn a_failing_clear_falls_back_to_ansi_and_keeps_rendering() {
+        let mut out: Vec<u8> = vec![];
+        let mut renderer = render::Renderer::new(&mut out, render::ScreenMode::Clear).with_clear(|| Err(clearscreen::Error::TerminfoCap("clear")));
+        assert_eq!(renderer.present("first").unwrap(), render::Presented::Warned);
+        assert!(renderer.warning().unwrap().contains("required terminfo capability not available: clear"));
+
+        // Warned about once, every frame after is drawn just the same.
+        for _ in 0..3 {
+            assert_eq!(renderer.present("next").unwrap(), render::Presented::Drawn);
+        }
+        drop(renderer);
+        let written = String::from_utf8(out).unwrap();
+        assert_eq!(written, format!("\x1b[H\x1b[2Jfirst{}", "\x1b[H\x1b[2Jnext".repeat(3)));
+    }
+
+    #[test]
+    fn enemies_hold_still_until_the_countdown_ends() {
+        let mut state = test_state(20, 40);
+        state.args.sim_rate = Some(10);
+        state.phase = Phase::Countdown(state.countdown_duration());
+        assert_eq!(state.countdown_duration(), 40);
+        let positions = |state: &GameState| state.enemies.iter().map(|alien| alien.pos).collect::<Vec<_>>();
+        let before = positions(&state);
+        let col = state.player.pos.col;
+
+        // The ship moves, nothing else does (firing included).
+        run_script(&mut state, &[&[Action::MoveLeft, Action::Fire] as &[Action]; 39]);
+        assert!(matches!(state.phase, Phase::Countdown(1)));
+        assert_eq!(positions(&state), before);
+        assert!(state.projectiles.is_empty());
+        assert_eq!(state.player.pos.col, col - 39.min(col));
+
+        state.tick(&[]);
+        assert_eq!(state.phase, Phase::Playing);
+        assert_eq!(positions(&state), before);
+        assert_eq!(state.formation, FormationPhase::EnteringFormation { frame: 0 });
+        state.tick(&[]);
+        assert_eq!(state.formation, FormationPhase::EnteringFormation { frame: 1 }, "entering formation on the first frame played");
+    }
+
+    #[test]
+    fn extra_lives_are_awarded_once_per_threshold_crossed() {
+        let args = Arguments { seed: Some(7), no_color: true, lives: 5, extra_life_every: Some(100), ..Arguments::default() };
+        let mut state = GameState::new(args.clone());
+        state.reset(args, Size { rows: 20, cols: 40 });
+        assert_eq!(state.lives, 5);
+
+        let extra_lives = |state: &GameState| state.events.iter().filter(|event| **event == events::Event::ExtraLife).count();
+        state.award(90);
+        assert_eq!((state.lives, extra_lives(&state)), (5, 0));
+        state.award(20);
+        assert_eq!((state.lives, extra_lives(&state)), (6, 1));
+        state.award(50);
+        assert_eq!((state.lives, state.extra_life_threshold), (6, 100), "none between two thresholds");
+
+        // Crossing two at once still awards both.
+        state.events.clear();
+        state.award(150);
+        assert_eq!((state.score, state.lives, extra_lives(&state)), (310, 8, 2));
+        assert_eq!(state.extra_life_threshold, 300);
+    }
+
+    #[test]
+    fn shielded_aliens_survive_their_first_hit() {
+        let mut state = test_state(20, 40);
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+        let alien = state.enemies.iter().find(|alien| alien.is_shielded()).unwrap().clone();
+        assert_eq!(alien.hp, 2);
+        state.enemies = vec![alien.clone()];
+        state.projectiles.clear();
+        state.enemy_direction = 0;
+        let rows = state.size.rows as usize;
+
+        // Cracked, for no points, and drawn as a regular alien from then on.
+        shoot_at(&mut state, alien.pos.col);
+        run_idle(&mut state, rows);
+        assert_eq!(state.enemies.len(), 1);
+        assert_eq!((state.enemies[0].hp, state.score), (1, 0));
+        assert!(!state.enemies[0].is_shielded());
+        assert!(capture(&state)[alien.pos.row as usize].contains(&state.glyphs.alien));
+
+        shoot_at(&mut state, alien.pos.col);
+        run_idle(&mut state, rows);
+        assert!(state.enemies.is_empty());
+        assert_eq!(state.score - state.wave_bonus, state.args.scoring.alien_points);
+    }
+
+    #[test]
+    fn restarting_from_the_pause_menu_starts_a_fresh_game() {
+        let mut state = test_state(20, 40);
+        run_script(&mut state, &[&[Action::MoveLeft, Action::Fire] as &[Action]; 30]);
+        state.score = 120;
+        state.tick(&[Action::Pause]);
+        assert_eq!(state.phase, Phase::Paused(menu::PauseMenu::default()));
+        let frame = state.frame;
+        state.tick(&[]);
+        assert_eq!(state.frame, frame, "the game holds still behind the menu");
+
+        // Down to "Restart", wrapping around past "Quit" and back.
+        run_script(&mut state, &[&[Action::MenuDown], &[Action::MenuDown], &[Action::MenuDown], &[Action::MenuDown]]);
+        assert_eq!(state.phase, Phase::Paused(menu::PauseMenu { selected: menu::PauseOption::Restart }));
+        assert!(capture(&state).iter().any(|line| line.contains("> Restart <")));
+        state.tick(&[Action::Confirm]);
+
+        let mut fresh = GameState::new(state.args.clone());
+        fresh.reset(state.args.clone(), Size { rows: 20, cols: 40 });
+        assert!(matches!(state.phase, Phase::Countdown(_)));
+        assert_eq!(save::write(&state), save::write(&fresh));
+        assert_eq!(state.stats, fresh.stats);
+    }
+
+    #[test]
+    fn foreground_entities_win_a_shared_cell() {
+        let mut state = test_state(20, 40);
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+        let alien = state.enemies.iter().find(|alien| !alien.is_shielded()).unwrap().clone();
+        state.enemies = vec![alien.clone()];
+        state.projectiles.clear();
+        let star = |pos: entities::Pos| entities::FallingStar { pos, entity: '*' };
+        let cell = |state: &GameState, pos: entities::Pos| capture(state)[pos.row as usize].chars().nth(pos.col as usize).unwrap();
+
+        // A star behind the alien's left-most cell, and another in the open.
+        let (behind, open) = (alien.pos, entities::Pos::new(alien.pos.row, alien.pos.col + 5));
+        state.falling_stars = vec![star(behind), star(open)];
+        assert_eq!(cell(&state, behind), '/');
+        assert_eq!(cell(&state, open), '*');
+
+        // Shots in front of aliens, the ship in front of shots.
+        let shot = |pos: entities::Pos, owner| entities::Projectile { pos, from_row: pos.row, owner, drift: 0, y: pos.row as f32, velocity: 0_f32 };
+        state.projectiles = vec![shot(behind, entities::Owner::Player), shot(state.player.pos, entities::Owner::Enemy)];
+        assert_eq!(cell(&state, behind), '|');
+        assert_eq!(cell(&state, state.player.pos), '/');
+    }
+
+    #[test]
+    fn star_counts_scale_with_the_terminals_area() {
+        let mut state = test_state(20, 40);
+        state.args.star_density = 20_f32;
+        state.seed_stars();
+        let stars = |state: &mut GameState, rows, cols| {
+            state.sizes = Box::new(MockSize(rows, cols));
+            state.evaluate_state().unwrap();
+            assert_eq!(state.falling_stars.len(), state.star_count(), "reseeded on resize");
+            return state.falling_stars.len();
+        };
+
+        // 20 per thousand cells, four times the cells making for four times the stars.
+        let small = stars(&mut state, 20, 40);
+        let large = stars(&mut state, 40, 80);
+        assert_eq!((small, large), (16, 64));
+        assert_eq!(stars(&mut state, 20, 80), 2 * small);
+
+        state.args.star_density = 0_f32;
+        assert_eq!(stars(&mut state, 24, 80), 0);
+    }
+
+    #[test]
+    fn only_errors_exit_with_a_code_of_their_own() {
+        let outcome = |result| Outcome { result, score: 0, wave: 1, stats: stats::SessionStats::default() };
+        assert_eq!(outcome(GameResult::Quit).exit_code(), 0);
+        assert_eq!(outcome(GameResult::Won).exit_code(), 0);
+        assert_eq!(outcome(GameResult::Lost).exit_code(), 0);
+
+        let errors = [GameError::Config(String::new()), GameError::Terminal(String::new()), GameError::Input(String::new()), GameError::Io(String::new())];
+        assert_eq!(errors.map(|error| error.exit_code()), [2, 3, 4, 5]);
+
+        // A benchmark runs to completion however the AI fared, while `start()` fails on a bad configuration.
+        let (_, outcome) = benchmark::run(Arguments { seed: Some(7), ..Arguments::default() }, 10);
+        assert_eq!(outcome.exit_code(), 0);
+        let stop = AtomicBool::new(false);
+        let glyphs = vec![(String::from("planet"), String::from("o"))];
+        assert_eq!(start(Arguments { glyphs, ..Arguments::default() }, &stop).unwrap_err().exit_code(), 2);
+    }
+
+    #[test]
+    fn an_invincible_ship_shrugs_off_hits() {
+        let mut state = test_state(20, 80);
+        (state.args.invincible, state.lives) = (true, 1);
+        assert!(render::status_line(0, &state).contains("GOD MODE"));
+
+        // Shot at from right above the ship.
+        let pos = entities::Pos::new(state.player_row() - 1, state.player.center());
+        state.projectiles = vec![entities::Projectile { pos, from_row: pos.row, owner: entities::Owner::Enemy, drift: 0, y: pos.row as f32, velocity: 0_f32 }];
+        run_idle(&mut state, 4);
+        assert!(state.projectiles.iter().all(|projectile| projectile.owner == entities::Owner::Player));
+        assert_eq!(state.lives, 1);
+        assert!(!state.events.contains(&events::Event::PlayerHit));
+        assert_eq!(state.phase, Phase::Playing);
+
+        // Aliens reaching the ship pass through it, rather than invading.
+        let row = state.invasion_row();
+        let invader = &mut state.enemies[0];
+        (invader.pos.row, invader.y, invader.target_row) = (row, row as f32, row);
+        run_idle(&mut state, 1);
+        assert_eq!((state.lives, state.phase), (1, Phase::Playing));
+    }
+
+    #[test]
+    fn ufos_appear_on_schedule_from_alternating_sides() {
+        let mut state = test_state(20, 40);
+        (state.args.ufo_interval, state.args.ufo_chance, state.args.invincible) = (20, 1_f32, true);
+        let appearance = |state: &mut GameState| {
+            while state.ufo.is_none() {
+                state.tick(&[]);
+                assert!(state.frame < 1000);
+            }
+            let ufo = state.ufo.take().unwrap();
+            return (state.frame, ufo.pos.col, ufo.direction);
+        };
+
+        assert_eq!(appearance(&mut state), (20, 0, 1));
+        assert_eq!(appearance(&mut state), (40, 37, -1));
+        assert_eq!(appearance(&mut state), (60, 0, 1));
+
+        // Left to chance, the seed decides which of the scheduled frames it shows up on.
+        let schedule = || {
+            let mut state = test_state(20, 40);
+            (state.args.ufo_interval, state.args.ufo_chance, state.args.invincible) = (20, 0.3_f32, true);
+            return (0..4).map(|_| appearance(&mut state)).collect::<Vec<_>>();
+        };
+        let frames = schedule();
+        assert!(frames.iter().all(|(frame, _, _)| frame % 20 == 0));
+        assert_eq!(frames, schedule());
+    }
+
+    #[test]
+    fn kills_leave_a_score_popup_behind_for_a_while() {
+        let mut state = test_state(20, 40);
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+        let back_row = state.enemies.iter().map(|alien| alien.pos.row).min().unwrap();
+        state.enemies.retain(|alien| alien.pos.row == back_row);
+        state.enemy_direction = 0;
+        state.projectiles.clear();
+        let alien = state.enemies[0].clone();
+        shoot_at(&mut state, alien.center());
+        state.projectiles[0].pos.row = alien.pos.row + 1;
+        state.tick(&[]);
+
+        let points = state.args.scoring.alien_points;
+        assert_eq!(state.popups.len(), 1);
+        let popup = &state.popups[0];
+        assert_eq!((popup.value, popup.pos, popup.frames_left), (points, entities::Pos::new(alien.pos.row, alien.center()), POPUP_FRAMES));
+        assert!(capture(&state)[alien.pos.row as usize].contains(&format!("+{points}")));
+
+        // Drifting up a row every other frame, until it's gone.
+        run_idle(&mut state, POPUP_FRAMES as usize - 1);
+        assert_eq!(state.popups.len(), 1);
+        assert!(state.popups[0].pos.row < alien.pos.row);
+        state.tick(&[]);
+        assert!(state.popups.is_empty());
+    }
+
+    #[test]
+    fn a_held_key_moves_the_ship_every_step_until_released() {
+        let mut state = test_state(20, 40);
+        let start = time::Instant::now();
+        let step = |n: u64| start + time::Duration::from_millis(20 * n);
+        let col = state.player.pos.col;
+
+        // A single press, never repeated, moves the ship on every step it's held for.
+        let mut held = input::HeldKeys::new(true);
+        held.press(Action::MoveLeft, start);
+        for n in 1..=10 {
+            state.tick(&held.movement(step(n)));
+        }
+        assert_eq!(state.player.pos.col, col - 10);
+        held.release(Action::MoveLeft);
+        run_script(&mut state, &[&held.movement(step(11)), &held.movement(step(12))]);
+        assert_eq!(state.player.pos.col, col - 10);
+
+        // Without releases reported, the key counts as held for a while after its latest press (or repeat).
+        let mut held = input::HeldKeys::new(false);
+        held.press(Action::MoveRight, start);
+        assert_eq!(held.movement(step(1)), [Action::MoveRight]);
+        assert_eq!(held.movement(step(24)), [Action::MoveRight]);
+        assert!(held.movement(step(25)).is_empty());
+
+        // Long enough to bridge the OS' delay before it starts repeating a key, the first repeat coming 300ms in.
+        let mut held = input::HeldKeys::new(false);
+        held.press(Action::MoveRight, start);
+        let mut moves = (1..=15).map(|n| held.movement(step(n))).collect::<Vec<_>>();
+        held.press(Action::MoveRight, step(15));
+        moves.extend((16..=30).map(|n| held.movement(step(n))));
+        assert!(moves.iter().all(|directions| directions == &[Action::MoveRight]), "{moves:?}");
+
+        // Unless told otherwise.
+        let mut held = input::HeldKeys::new(false).with_timeout(time::Duration::from_millis(150));
+        held.press(Action::MoveRight, start);
+        assert_eq!(held.movement(step(7)), [Action::MoveRight]);
+        assert!(held.movement(step(8)).is_empty());
+
+        // A tap shorter than a step still moves it, once.
+        let mut held = input::HeldKeys::new(true);
+        held.press(Action::MoveRight, start);
+        held.release(Action::MoveRight);
+        assert_eq!(held.movement(step(1)), [Action::MoveRight]);
+        assert!(held.movement(step(2)).is_empty());
+    }
+
+    #[test]
+    fn enemy_fire_respects_the_projectile_cap() {
+        let mut state = test_state(20, 40);
+        state.args.max_enemy_projectiles = Some(2);
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+        state.projectiles.clear();
+        let in_flight = |state: &GameState| state.projectiles.iter().filter(|projectile| projectile.owner == entities::Owner::Enemy).count();
+
+        for _ in 0..10 {
+            state.enemy_fire();
+            assert!(in_flight(&state) <= 2);
+        }
+        assert_eq!(in_flight(&state), 2);
+
+        // Room is made as shots leave the play area.
+        state.projectiles.remove(0);
+        state.enemy_fire();
+        assert_eq!(in_flight(&state), 2);
+
+        // Uncapped, every attempt fires.
+        state.args.max_enemy_projectiles = None;
+        for _ in 0..10 {
+            state.enemy_fire();
+        }
+        assert_eq!(in_flight(&state), 12);
+    }
+
+    #[test]
+    fn frame_time_percentiles_come_from_the_recorded_samples() {
+        let mut times = profile::FrameTimes::new(100);
+        assert_eq!(times.percentile(50_f32), None);
+        for millis in 1..=100 {
+            times.record(time::Duration::from_millis(millis));
+        }
+
+        let ms = time::Duration::from_millis;
+        assert_eq!([50_f32, 95_f32, 99_f32, 100_f32].map(|percentile| times.percentile(percentile)), [Some(ms(50)), Some(ms(95)), Some(ms(99)), Some(ms(100))]);
+        assert_eq!(times.histogram(), [9, 10, 30, 50, 1, 0]);
+        assert_eq!(times.report()[1], "  p50: 50.0ms | p95: 95.0ms | p99: 99.0ms");
+
+        // Bounded, the oldest samples making way.
+        for _ in 0..50 {
+            times.record(ms(300));
+        }
+        assert_eq!(times.len(), 100);
+        assert_eq!(times.percentile(50_f32), Some(ms(100)));
+        assert_eq!(times.histogram()[5], 50);
+    }
+
+    #[test]
+    fn a_bounce_eases_the_formation_down_a_row() {
+        let mut state = test_state(20, 40);
+        state.args.invincible = true;
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+        let mut alien = state.enemies.iter().find(|alien| !alien.is_shielded()).unwrap().clone();
+        alien.pos.col = 40 - alien.width;
+        let (row, col) = (alien.pos.row, alien.pos.col);
+        state.enemies = vec![alien];
+        while state.descent == 0 {
+            state.tick(&[]);
+        }
+        assert_eq!(state.enemy_direction, -1);
+
+        // A quarter of a row a frame, drawn on whichever row it's nearest to.
+        let drawn = |state: &GameState| (0..20).find(|row| capture(state)[*row].contains(&state.glyphs.alien)).unwrap() as u16;
+        let mut frames = vec![];
+        for _ in 0..DESCENT_FRAMES {
+            state.tick(&[]);
+            let alien = &state.enemies[0];
+            assert_eq!(alien.pos.col, col);
+            frames.push((alien.y, alien.pos.row, drawn(&state)));
+        }
+        let (r, next) = (row as f32, row + 1);
+        assert_eq!(frames, [(r + 0.25, row, row), (r + 0.5, next, next), (r + 0.75, next, next), (r + 1_f32, next, next)]);
+        assert_eq!(state.descent, 0);
+    }
+
+    #[test]
+    fn a_starless_game_has_no_stars_to_draw() {
+        let args = Arguments { seed: Some(7), no_color: true, stars: false, star_density: 200_f32, ..Arguments::default() };
+        let mut state = GameState::new(args.clone());
+        state.reset(args, Size { rows: 20, cols: 40 });
+        state.phase = Phase::Playing;
+        assert!(state.falling_stars.is_empty());
+
+        state.sizes = Box::new(MockSize(24, 60));
+        state.evaluate_state().unwrap();
+        run_idle(&mut state, 30);
+        assert!(state.falling_stars.is_empty());
+        assert!(capture(&state).iter().all(|line| !line.contains(state.glyphs.star)));
+    }
+
+    #[test]
+    fn alien_skins_animate_through_their_packs_frames() {
+        let args = Arguments { seed: Some(7), no_color: true, alien_skin: Some(sprites::Skin::Crabs), ..Arguments::default() };
+        let mut state = GameState::new(args.clone());
+        state.reset(args, Size { rows: 20, cols: 40 });
+        state.phase = Phase::Playing;
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+        let back_row = state.enemies.iter().filter(|alien| alien.rank == 0).map(|alien| alien.pos.row).next().unwrap() as usize;
+        assert_eq!(state.marches % 2, 0);
+        assert!(capture(&state)[back_row].contains("{@} {@}"));
+        assert!(capture(&state)[back_row + 1].contains("/M\\"));
+
+        // Every march flips to the other frame.
+        let marches = state.marches;
+        while state.marches == marches {
+            state.tick(&[]);
+        }
+        assert!(capture(&state)[back_row].contains("}@{ }@{"));
+        assert!(capture(&state)[back_row + 1].contains("\\M/"));
+        assert!(!capture(&state).join("\n").contains(state.glyphs.alien.as_str()));
+    }
+
+    #[test]
+    fn every_out_of_range_argument_is_rejected_by_name() {
+        type Invalidate = fn(&mut Arguments);
+        let invalid: [(&str, Invalidate); 32] = [
+            ("frame_rate", |args| args.frame_rate = FrameRate::Capped(0)),
+            ("frame_rate", |args| args.frame_rate = FrameRate::Capped(1000)),
+            ("hud_rate", |args| args.hud_rate = Some(0)),
+            ("sim_rate", |args| args.sim_rate = Some(0)),
+            ("projectile_speed", |args| args.projectile_speed = 0),
+            ("enemy_projectile_speed", |args| args.enemy_projectile_speed = 0),
+            ("enemy_time", |args| (args.enemy_time, args.min_enemy_time) = (0, 0)),
+            ("min_enemy_time", |args| args.min_enemy_time = 0),
+            ("min_enemy_time", |args| args.min_enemy_time = args.enemy_time + 1),
+            ("lives", |args| args.lives = 0),
+            ("extra_life_every", |args| args.extra_life_every = Some(0)),
+            ("gravity", |args| args.gravity = -0.5),
+            ("gravity", |args| args.gravity = f32::NAN),
+            ("speed_growth", |args| args.difficulty.speed_growth = -1_f32),
+            ("fire_growth", |args| args.difficulty.fire_growth = f32::INFINITY),
+            ("max_rows", |args| args.difficulty.max_rows = 0),
+            ("invasion_row_offset", |args| args.invasion_row_offset = args.min_rows / 2),
+            ("min_rows", |args| args.min_rows = 9),
+            ("min_cols", |args| args.min_cols = 19),
+            ("arena_growth", |args| args.arena_growth = Some(0)),
+            ("time_limit", |args| args.time_limit = Some(0)),
+            ("total_waves", |args| args.total_waves = Some(0)),
+            ("fire_bias", |args| args.fire_bias = -1_f32),
+            ("star_density", |args| args.star_density = 1001_f32),
+            ("background_char", |args| args.background_char = '\t'),
+            ("background_char", |args| args.background_char = '漢'),
+            ("input_capacity", |args| args.input_capacity = 0),
+            ("hold_timeout", |args| args.hold_timeout = 0),
+            ("respawn_delay", |args| args.respawn_delay = Some(0)),
+            ("ufo_chance", |args| args.ufo_chance = 1.5),
+            ("ufo_speed", |args| args.ufo_speed = 0),
+            ("benchmark", |args| args.benchmark = Some(0)),
+        ];
+
+        assert_eq!(Arguments::default().validate(), Ok(()));
+        for (field, invalidate) in invalid {
+            let mut args = Arguments::default();
+            invalidate(&mut args);
+            match args.validate() {
+                Err(cli::ArgError::OutOfRange { name, .. }) => assert_eq!(name, field),
+                Ok(()) => panic!("'{field}' should have been rejected"),
+            }
+        }
+
+        let error = Arguments { lives: 0, ..Arguments::default() }.validate().unwrap_err();
+        assert_eq!(error.to_string(), "Invalid value '0' for 'lives', expected at least 1");
+    }
+
+    #[test]
+    fn the_arena_grows_with_score_and_keeps_everything_inside() {
+        let args = Arguments { seed: Some(7), no_color: true, arena_growth: Some(100), min_rows: 20, min_cols: 60, ..Arguments::default() };
+        let mut state = GameState::new(args.clone());
+        state.reset(args, Size { rows: 40, cols: 120 });
+        state.phase = Phase::Playing;
+        assert_eq!((state.size.rows, state.size.cols), (20, 60), "starts at the minimum");
+
+        state.player.pos.col = 59;
+        state.award(250);
+        run_idle(&mut state, 1);
+        assert_eq!((state.size.rows, state.size.cols), (24, 76), "two steps grown");
+
+        let size = Size { rows: state.size.rows, cols: state.size.cols };
+        assert_eq!(state.player.pos.row, state.player_row(), "the ship follows the arena's new bottom");
+        assert!(state.player.pos.in_bounds(&size));
+        assert!(state.enemies.iter().all(|alien| alien.pos.in_bounds(&size)));
+        assert!(state.projectiles.iter().all(|projectile| projectile.pos.in_bounds(&size)));
+        assert!(state.falling_stars.iter().all(|star| star.pos.in_bounds(&size)));
+
+        // Never past the terminal, however high the score.
+        state.award(100_000);
+        run_idle(&mut state, 1);
+        assert_eq!((state.size.rows, state.size.cols), (40, 120));
+    }
+
+    #[test]
+    fn positions_are_checked_and_clamped_against_a_size() {
+        use entities::Pos;
+        let size = Size { rows: 10, cols: 20 };
+
+        assert!(Pos::new(0, 0).in_bounds(&size));
+        assert!(Pos::new(9, 19).in_bounds(&size), "the last cell");
+        assert!(!Pos::new(10, 19).in_bounds(&size), "one row past");
+        assert!(!Pos::new(9, 20).in_bounds(&size), "one column past");
+
+        assert_eq!(Pos::new(4, 7).clamped(&size), Pos::new(4, 7), "cells inside are left alone");
+        assert_eq!(Pos::new(25, 7).clamped(&size), Pos::new(9, 7), "rows and columns are clamped separately");
+        assert_eq!(Pos::new(4, 70).clamped(&size), Pos::new(4, 19));
+        assert_eq!(Pos::new(u16::MAX, u16::MAX).clamped(&size), Pos::new(9, 19));
+        assert!(Pos::new(u16::MAX, 3).clamped(&size).in_bounds(&size));
+    }
+
+    #[test]
+    fn entity_at_reports_whatever_is_drawn_in_front() {
+        use entities::Pos;
+        let mut state = test_state(20, 40);
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+        let alien = state.enemies[0].clone();
+        state.enemies = vec![alien.clone()];
+        state.projectiles.clear();
+        let star = |pos: Pos| entities::FallingStar { pos, entity: '*' };
+        let shot = |pos: Pos, owner| entities::Projectile { pos, from_row: pos.row, owner, drift: 0, y: pos.row as f32, velocity: 0_f32 };
+
+        let top = state.play_top();
+        let ufo = Pos::new(top, 30);
+        state.ufo = Some(entities::Ufo { pos: ufo, from_col: ufo.col, width: 3, direction: 1 });
+        let (open, behind_alien, behind_ufo) = (Pos::new(alien.pos.row + 2, 1), alien.pos, Pos::new(top, 31));
+        state.falling_stars = vec![star(open), star(behind_alien), star(behind_ufo)];
+
+        assert_eq!(state.entity_at(open), Some(EntityRef::Star(0)));
+        assert_eq!(state.entity_at(Pos::new(open.row, open.col + 1)), None, "nothing there");
+        assert_eq!(state.entity_at(behind_alien), Some(EntityRef::Alien(0)), "aliens in front of stars");
+        assert_eq!(state.entity_at(behind_ufo), Some(EntityRef::Ufo));
+        assert_eq!(state.entity_at(Pos::new(alien.pos.row, alien.pos.col + alien.width - 1)), Some(EntityRef::Alien(0)), "any of its cells");
+
+        // Shots in front of aliens, the ship in front of shots.
+        let ship = state.player.pos;
+        state.projectiles = vec![shot(ship, entities::Owner::Enemy), shot(behind_alien, entities::Owner::Player)];
+        assert_eq!(state.entity_at(behind_alien), Some(EntityRef::Projectile(1)));
+        assert_eq!(state.entity_at(ship), Some(EntityRef::Player));
+        assert_eq!(state.entity_at(Pos::new(ship.row, ship.col + state.player.width - 1)), Some(EntityRef::Player));
+    }
+
+    #[test]
+    fn the_inspector_panel_shows_live_state_beside_the_arena() {
+        let mut state = test_state(20, 120);
+        let joined = |state: &GameState| capture(state).join("\n");
+        assert!(!joined(&state).contains("INSPECTOR"), "closed to begin with");
+
+        state.tick(&[Action::ToggleInspector]);
+        assert!(state.inspector.is_some());
+        assert_eq!(state.size.cols, 120 - inspector::PANEL_WIDTH, "the arena makes room for the panel");
+
+        // Cursor onto the ship, from the top-left corner.
+        let ship = state.player.pos;
+        let mut moves = vec![Action::CursorDown; ship.row as usize];
+        moves.extend(vec![Action::CursorRight; ship.col as usize]);
+        state.tick(&moves);
+        let frame = joined(&state);
+        for line in ["INSPECTOR (F1)", &format!("Frame:     {}", state.frame), &format!("Player:    {},{}", ship.row, ship.col), "Under:     player"] {
+            assert!(frame.contains(line), "missing {line:?}");
+        }
+
+        state.tick(&[Action::ToggleInspector]);
+        assert!(!joined(&state).contains("INSPECTOR"));
+        assert_eq!(state.size.cols, 120, "the arena takes the terminal back");
+
+        // No room for the panel, only a hint to widen the terminal.
+        let mut narrow = test_state(20, 40);
+        narrow.tick(&[Action::ToggleInspector]);
+        assert!(!joined(&narrow).contains("INSPECTOR (F1)"));
+        assert!(render::status_line(0, &narrow).contains("INSPECTOR (widen terminal)"));
+    }
+
+    #[test]
+    fn only_the_front_alien_of_a_column_fires_under_the_classic_rule() {
+        let mut state = test_state(20, 40);
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+        let template = state.enemies[0].clone();
+        let at = |row: u16, col: u16| entities::Alien { pos: entities::Pos::new(row, col), y: row as f32, target_row: row, ..template.clone() };
+
+        // A column of three, and a lone alien off to the side.
+        state.enemies = vec![at(3, 10), at(5, 10), at(7, 10), at(3, 20)];
+        state.args.fire_rule = FireRule::FrontRow;
+        assert_eq!(state.eligible_shooters(), vec![2, 3], "the bottom of the column, and the lone alien");
+
+        // Offset by a cell, the sprites still overlap, so the one behind is still covered.
+        state.enemies[2].pos.col = 11;
+        assert_eq!(state.eligible_shooters(), vec![2, 3]);
+
+        state.args.fire_rule = FireRule::Any;
+        assert_eq!(state.eligible_shooters(), vec![0, 1, 2, 3]);
+
+        // Every shot under the classic rule comes from below the front alien.
+        state.args.fire_rule = FireRule::FrontRow;
+        for _ in 0..50 {
+            state.projectiles.clear();
+            state.enemy_fire();
+            assert!(state.projectiles.iter().all(|shot| shot.pos.row > 7 || shot.pos.col >= 20), "{:?}", state.projectiles);
+        }
+    }
+
+    #[test]
+    fn moving_the_mouse_centers_the_ship_on_its_column() {
+        use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+        let mouse = |kind, column| MouseEvent { kind, column, row: 5, modifiers: KeyModifiers::NONE };
+        let mut state = test_state(20, 40);
+        let width = state.player.width;
+
+        let moved = input::map_mouse(mouse(MouseEventKind::Moved, 20));
+        assert_eq!(moved, Some(Action::MoveTo(20)));
+        state.tick(&[moved.unwrap()]);
+        assert_eq!(state.player.pos.col, 20 - width / 2, "centered on the pointer");
+        assert!(state.player.occupies(20));
+
+        let dragged = input::map_mouse(mouse(MouseEventKind::Drag(MouseButton::Left), 3)).unwrap();
+        state.tick(&[dragged]);
+        assert!(state.player.occupies(3), "dragging moves it too");
+
+        // Past the edge, the ship stays on screen.
+        state.tick(&[input::map_mouse(mouse(MouseEventKind::Moved, 39)).unwrap()]);
+        assert_eq!(state.player.pos.col, 40 - width);
+
+        assert_eq!(input::map_mouse(mouse(MouseEventKind::Down(MouseButton::Left), 7)), Some(Action::Fire));
+        assert_eq!(input::map_mouse(mouse(MouseEventKind::ScrollUp, 7)), None);
+    }
+
+    /** Counts the frames presented to it, asking the loop to stop once `frames` of them were. */
+    struct StopAfter<'a> {
+        frames: u32,
+        stop: &'a AtomicBool,
+    }
+
+    impl io::Write for StopAfter<'_> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            return Ok(buf.len());
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            self.frames = self.frames.saturating_sub(1);
+            if self.frames == 0 {
+                self.stop.store(true, Ordering::Relaxed);
+            }
+            return Ok(());
+        }
+    }
+
+    #[test]
+    fn a_host_can_stop_the_loop_mid_game() {
+        let args = Arguments { seed: Some(7), force_size: Some((20, 40)), panic_on_errors: false, frame_rate: FrameRate::Capped(120), sim_rate: None, ..Arguments::default() };
+        let mut state = init_state(args).unwrap();
+        state.phase = Phase::Playing;
+        let stop = AtomicBool::new(false);
+
+        let renderer = render::Renderer::new(StopAfter { frames: 5, stop: &stop }, render::ScreenMode::Ansi);
+        let outcome = run_loop(&mut state, renderer, false, &stop).unwrap();
+
+        assert!(stop.load(Ordering::Relaxed), "stopped by the flag, after the fifth frame");
+        assert_eq!(outcome.result, GameResult::Quit, "a game in progress ends as quit");
+        assert_eq!(state.phase, Phase::Playing, "left as it was");
+        assert!(state.frame > 0, "the game ran in the meantime");
+    }
+
+    #[test]
+    fn keys_map_to_the_same_actions_whatever_the_platforms_event_shapes() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers};
+        let key = |code, modifiers, kind| KeyEvent { code, modifiers, kind, state: KeyEventState::NONE };
+        let actions = |events: &[KeyEvent]| events.iter().filter_map(|event| input::map_key(*event, input::Keymap::Game)).collect::<Vec<Action>>();
+
+        // Holding `space`, as Unix terminals report it by default (repeats as further presses),
+        // with keyboard enhancements (repeats as repeats, then a release) and as Windows' console does.
+        let space = |kind| key(KeyCode::Char(' '), KeyModifiers::NONE, kind);
+        let legacy = [space(KeyEventKind::Press), space(KeyEventKind::Press), space(KeyEventKind::Press)];
+        let enhanced = [space(KeyEventKind::Press), space(KeyEventKind::Repeat), space(KeyEventKind::Repeat), space(KeyEventKind::Release)];
+        let windows = [space(KeyEventKind::Press), space(KeyEventKind::Press), space(KeyEventKind::Press), space(KeyEventKind::Release)];
+        for shape in [&legacy[..], &enhanced, &windows] {
+            assert_eq!(actions(shape), [Action::Fire; 3], "{shape:?}");
+        }
+
+        // Ctrl+C quits however the modifiers come along (Windows adds the key's state, for example).
+        let ctrl_c = |modifiers| key(KeyCode::Char('c'), modifiers, KeyEventKind::Press);
+        assert_eq!(actions(&[ctrl_c(KeyModifiers::CONTROL)]), [Action::Quit]);
+        assert_eq!(actions(&[KeyEvent { state: KeyEventState::NUM_LOCK, ..ctrl_c(KeyModifiers::CONTROL) }]), [Action::Quit]);
+        assert_eq!(actions(&[ctrl_c(KeyModifiers::CONTROL | KeyModifiers::SHIFT)]), [Action::Quit]);
+        assert_eq!(actions(&[ctrl_c(KeyModifiers::NONE)]), [Action::Continue], "plain `c` continues a saved game");
+
+        // Releases alone never map to anything, they only end a held movement key (see `HeldKeys`).
+        assert!(actions(&[key(KeyCode::Left, KeyModifiers::NONE, KeyEventKind::Release)]).is_empty());
+        assert_eq!(actions(&[key(KeyCode::Left, KeyModifiers::NONE, KeyEventKind::Repeat)]), [Action::MoveLeft]);
+    }
+
+    #[test]
+    fn the_starfield_only_drifts_while_paused_when_asked_to() {
+        let mut state = test_state(20, 40);
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+        state.tick(&[Action::Pause]);
+        assert!(matches!(state.phase, Phase::Paused(_)));
+        let stars = |state: &GameState| state.falling_stars.iter().map(|star| star.pos).collect::<Vec<entities::Pos>>();
+        let aliens = |state: &GameState| state.enemies.iter().map(|alien| alien.pos).collect::<Vec<entities::Pos>>();
+        let (before, formation, frame) = (stars(&state), aliens(&state), state.frame);
+        assert!(!before.is_empty());
+
+        run_idle(&mut state, 5);
+        assert_eq!(stars(&state), before, "frozen behind the pause menu");
+
+        state.args.stars_animate_while_paused = true;
+        run_idle(&mut state, 1);
+        assert_ne!(stars(&state), before, "drifting behind the pause menu");
+        assert!(before.iter().zip(stars(&state)).all(|(old, new)| new.row == old.row + 1 || new.row == state.play_top()));
+
+        // Either way, the game itself stays paused.
+        assert!(matches!(state.phase, Phase::Paused(_)));
+        assert_eq!((aliens(&state), state.frame), (formation, frame));
+    }
+
+    #[test]
+    fn later_waves_shift_the_aliens_hue() {
+        let mut state = test_state(20, 40);
+        state.args.no_color = false;
+        let alien = state.enemies[0].clone();
+        state.enemies = vec![alien.clone()];
+        let color = |state: &mut GameState, wave: u16| {
+            state.wave = wave;
+            return state.compose_grid().cell(alien.pos.row, alien.pos.col).unwrap().color;
+        };
+
+        let base = state.theme().alien_color(alien.pos.row, state.play_top(), state.player_row());
+        assert_eq!(color(&mut state, 1), Some(base), "the first wave isn't shifted");
+        assert_eq!(color(&mut state, 5), Some(base.hue_rotate(colors::wave_shift(5))));
+        assert_ne!(color(&mut state, 1), color(&mut state, 5));
+        assert_eq!(color(&mut state, 10), color(&mut state, 1), "cycling back around after nine");
+
+        // Opted out of, and without colors altogether, every wave looks the same.
+        state.args.wave_colors = false;
+        assert_eq!(color(&mut state, 5), Some(base));
+        state.args.wave_colors = true;
+        state.args.no_color = true;
+        assert_eq!((color(&mut state, 1), color(&mut state, 5)), (None, None));
+    }
+
+    #[test]
+    fn a_campaign_moves_through_every_phase_in_order() {
+        let mut state = test_state(20, 80);
+        (state.args.total_waves, state.args.sim_rate) = (Some(2), Some(10));
+        state.phase = Phase::Title;
+        run_idle(&mut state, 3);
+        assert_eq!(state.phase, Phase::Title, "waiting on the player");
+
+        state.tick(&[Action::Fire]);
+        let countdown = state.countdown_duration();
+        assert_eq!(state.phase, Phase::Countdown(countdown));
+        state.tick(&[]);
+        assert_eq!(state.phase, Phase::Countdown(countdown - 1), "counting down a frame per tick");
+        run_idle(&mut state, countdown as usize - 1);
+        assert_eq!(state.phase, Phase::Playing);
+
+        state.tick(&[Action::Pause]);
+        assert_eq!(state.phase, Phase::Paused(menu::PauseMenu::default()));
+        state.tick(&[Action::Confirm]);
+        assert_eq!(state.phase, Phase::Playing, "resumed from the menu");
+
+        state.enemies.clear();
+        state.tick(&[]);
+        let interlude = state.interlude_duration();
+        assert_eq!(state.phase, Phase::WaveCleared(interlude));
+        run_idle(&mut state, interlude as usize - 1);
+        assert_eq!(state.phase, Phase::WaveCleared(1));
+        state.tick(&[]);
+        assert_eq!((state.phase, state.wave), (Phase::Playing, 2), "on to the next wave");
+
+        state.enemies.clear();
+        state.tick(&[]);
+        assert_eq!(state.phase, Phase::Won, "the last wave ends the campaign");
+        run_idle(&mut state, 3);
+        assert_eq!(state.phase, Phase::Won, "until the player moves on");
+
+        // Losing the last life ends it otherwise.
+        let mut state = test_state(20, 80);
+        state.lives = 1;
+        state.projectiles = vec![entities::Projectile { pos: state.player.pos, from_row: state.player.pos.row, owner: entities::Owner::Enemy, drift: 0, y: state.player.pos.row as f32, velocity: 0_f32 }];
+        state.tick(&[]);
+        assert_eq!(state.phase, Phase::GameOver);
+    }
+
+    #[test]
+    fn a_throttled_hud_only_recomposes_on_its_refresh_frames() {
+        // Refreshed twice a second at 60 frames a second, so every 30th frame.
+        let mut hud = render::Hud::new(FrameRate::Capped(60), Some(2));
+        let mut composed = vec![];
+        let mut shown = vec![];
+        for frame in 0..90 {
+            shown.push(hud.status(|| {
+                composed.push(frame);
+                return format!("Frame {frame}");
+            }).to_string());
+        }
+        assert_eq!(composed, [0, 30, 60]);
+        assert!(shown[..30].iter().all(|status| status == "Frame 0"), "cached in between");
+        assert_eq!((shown[30].as_str(), shown[89].as_str()), ("Frame 30", "Frame 60"));
+
+        // Without a rate (or one at least as fast as the frames), every frame.
+        for (frame_rate, hud_rate) in [(FrameRate::Capped(60), None), (FrameRate::Capped(60), Some(0)), (FrameRate::Capped(10), Some(30))] {
+            let mut hud = render::Hud::new(frame_rate, hud_rate);
+            let mut composed = 0;
+            for _ in 0..10 {
+                hud.status(|| {
+                    composed += 1;
+                    return String::new();
+                });
+            }
+            assert_eq!(composed, 10, "{frame_rate:?} {hud_rate:?}");
+        }
+    }
+
+    #[test]
+    fn scaling_the_arena_down_pulls_everything_back_inside() {
+        let args = Arguments { seed: Some(7), no_color: true, min_rows: 20, min_cols: 60, ..Arguments::default() };
+        let mut state = GameState::new(args.clone());
+        state.reset(args, Size { rows: 40, cols: 120 });
+        state.phase = Phase::Playing;
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+
+        // Everything as far right as it goes.
+        state.player.pos.col = 120 - state.player.width;
+        for alien in state.enemies.iter_mut() {
+            alien.pos.col += 120 - alien.bounds().col - alien.bounds().w;
+        }
+        state.projectiles = vec![entities::Projectile { pos: entities::Pos::new(30, 119), from_row: 30, owner: entities::Owner::Player, drift: 0, y: 30_f32, velocity: 0_f32 }];
+
+        state.tick(&[Action::ScaleDown, Action::ScaleDown]);
+        assert_eq!((state.size.rows, state.size.cols), (36, 104), "two steps smaller");
+        let cols = state.size.cols;
+        assert_eq!(state.player.pos.col, cols - state.player.width);
+        assert_eq!(state.player.pos.row, state.player_row());
+        assert!(state.enemies.iter().all(|alien| alien.bounds().col + alien.bounds().w <= cols));
+        assert!(state.projectiles.is_empty(), "shots left outside are dropped");
+        assert!(state.falling_stars.iter().all(|star| star.pos.col < cols && star.pos.row < state.size.rows));
+
+        // Never below the minimum, and back up to the terminal.
+        state.tick(&[Action::ScaleDown; 20]);
+        assert_eq!((state.size.rows, state.size.cols), (20, 60));
+        state.tick(&[Action::ScaleUp; 20]);
+        assert_eq!((state.size.rows, state.size.cols), (40, 120));
+    }
+
+    #[test]
+    fn destroyed_aliens_are_reported_by_their_id() {
+        let mut state = test_state(20, 40);
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+        state.projectiles.clear();
+        let ids: Vec<u32> = state.enemies.iter().map(|alien| alien.id).collect();
+        assert_eq!(ids, (1..=ids.len() as u32).collect::<Vec<u32>>(), "counting up from one as they spawn");
+
+        // A front-row alien, with the first one in the vector removed beforehand to shuffle the others along.
+        let front = state.enemies.iter().map(|alien| alien.rank).max().unwrap();
+        let target = state.enemies.iter_mut().find(|alien| alien.rank == front && alien.id > 1).unwrap();
+        target.hp = 1;
+        let target = target.clone();
+        state.enemies.remove(0);
+        let row = target.pos.row + 1;
+        state.projectiles.push(entities::Projectile { pos: entities::Pos::new(row, target.center()), from_row: row, owner: entities::Owner::Player, drift: 0, y: row as f32, velocity: -1_f32 });
+        state.tick(&[]);
+
+        let destroyed: Vec<u32> = state.events.iter().filter_map(|event| match event {
+            events::Event::AlienDestroyed { id, .. } => Some(*id),
+            _ => None,
+        }).collect();
+        assert_eq!(destroyed, [target.id]);
+        assert!(state.enemies.iter().all(|alien| alien.id != target.id), "gone for good");
+        assert_eq!(state.enemies.len(), ids.len() - 2);
+        assert!(state.enemies.iter().all(|alien| ids.contains(&alien.id) && alien.id != 1), "the others keep theirs");
+    }
+
+    #[test]
+    fn hardcore_runs_get_one_life_no_rewards_and_a_leaderboard_of_their_own() {
+        let path = std::env::temp_dir().join(format!("clinvaders-hardcore-{}", std::process::id()));
+        let hardcore = std::path::PathBuf::from(format!("{}.hardcore", path.display()));
+        let args = Arguments {
+            seed: Some(7),
+            force_size: Some((20, 40)),
+            hardcore: true,
+            lives: 5,
+            extra_life_every: Some(100),
+            leaderboard: Some(path.clone()),
+            ..Arguments::default()
+        };
+        let mut state = init_state(args).unwrap();
+        state.phase = Phase::Playing;
+        assert_eq!(state.lives, 1, "whatever `lives` says");
+
+        // Past a bullet time threshold and several extra life ones, without either.
+        state.award(300);
+        assert_eq!((state.slowdown, state.lives), (0, 1));
+        assert!(!state.events.contains(&events::Event::ExtraLife));
+        let mut regular = test_state(20, 40);
+        regular.args.extra_life_every = Some(100);
+        regular.award(300);
+        assert_eq!((regular.slowdown, regular.lives), (regular.slowdown_duration(), 6), "where a regular run is rewarded");
+
+        // A single hit ends it, the score being filed under the hardcore leaderboard.
+        state.projectiles = vec![entities::Projectile { pos: state.player.pos, from_row: state.player.pos.row, owner: entities::Owner::Enemy, drift: 0, y: state.player.pos.row as f32, velocity: 0_f32 }];
+        game_loop(&mut state, vec![]).unwrap();
+        assert_eq!(state.phase, Phase::GameOver);
+        game_loop(&mut state, vec![Action::Type('H'), Action::Type('C'), Action::Type('R'), Action::Confirm]).unwrap();
+
+        let filed = std::fs::read_to_string(&hardcore).unwrap();
+        assert!(filed.starts_with(&format!("{},HCR,", state.score)), "{filed:?}");
+        assert!(!path.exists(), "nothing on the regular leaderboard");
+        std::fs::remove_file(&hardcore).unwrap();
+    }
+
+    #[test]
+    fn qualifying_scores_are_placed_in_order_on_a_top_ten() {
+        let entry = |score: u32, initials: &str| leaderboard::Entry { score, initials: String::from(initials), date: String::from("2026-10-14") };
+        let mut board = leaderboard::Leaderboard::default();
+        for score in [300, 1000, 100, 700, 500, 900, 200, 800, 400, 600] {
+            assert!(board.insert(entry(score, "AAA")).is_some());
+        }
+        assert!(!board.qualifies(100), "no better than the bottom score");
+        assert!(board.qualifies(550));
+
+        // Typed in on game over, the initials place the score between the two around it.
+        let mut typing = leaderboard::InitialsEntry::default();
+        let typed: Vec<Option<String>> = [Action::Type('j'), Action::Type('d'), Action::Confirm, Action::Type('x'), Action::Confirm]
+            .into_iter()
+            .map(|action| typing.handle(action))
+            .collect();
+        assert_eq!(typed, [None, None, None, None, Some(String::from("JDX"))], "confirmed only once all three are in");
+        assert_eq!(board.insert(entry(550, "JDX")), Some(5));
+
+        let lines = board.lines();
+        assert_eq!(lines.len(), 1 + leaderboard::MAX_ENTRIES, "truncated to ten, below its heading");
+        let scores: Vec<u32> = lines[1..].iter().map(|line| line.split_whitespace().nth(2).unwrap().parse().unwrap()).collect();
+        assert_eq!(scores, [1000, 900, 800, 700, 600, 550, 500, 400, 300, 200], "the lowest fell off");
+        assert_eq!(lines[6], " 6. JDX    550  2026-10-14");
+
+        // Kept across sessions, and shown on the title screen.
+        let path = std::env::temp_dir().join(format!("clinvaders-leaderboard-{}", std::process::id()));
+        board.save(&path).unwrap();
+        assert_eq!(leaderboard::Leaderboard::load(&path).unwrap(), board);
+        std::fs::remove_file(&path).unwrap();
+
+        let mut state = test_state(30, 60);
+        state.phase = Phase::Title;
+        state.leaderboard = Some(board);
+        let frame = capture(&state).join("\n");
+        assert!(frame.contains("HIGH SCORES") && frame.contains("JDX"));
+    }
+
+    #[test]
+    fn zig_zag_shots_sidestep_back_and_forth_on_the_way_down() {
+        let mut state = test_state(30, 40);
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+        (state.args.enemy_bullet_pattern, state.args.enemy_projectile_speed, state.args.max_enemy_projectiles) = (BulletPattern::ZigZag, 2, Some(1));
+        let shot = |row: u16, col: u16, drift: i8| entities::Projectile { pos: entities::Pos::new(row, col), from_row: row, owner: entities::Owner::Enemy, drift, y: row as f32, velocity: 0_f32 };
+
+        let start = state.enemies.iter().map(|alien| alien.pos.row).max().unwrap() + 1;
+        state.projectiles = vec![shot(start, 10, 1)];
+        let mut path = vec![(start, 10)];
+        while path.len() < 6 {
+            state.tick(&[]);
+            let pos = state.projectiles[0].pos;
+            if pos.row != path.last().unwrap().0 {
+                path.push((pos.row, pos.col));
+            }
+        }
+        let rows: Vec<u16> = path.iter().map(|(row, _)| *row).collect();
+        let cols: Vec<u16> = path.iter().map(|(_, col)| *col).collect();
+        assert_eq!(rows, (0..6).map(|step| start + 2 * step).collect::<Vec<u16>>(), "two rows a step");
+        assert_eq!(cols, [10, 11, 10, 11, 10, 11], "a column to either side, alternating");
+
+        // Bouncing off the edge rather than leaving the screen.
+        state.projectiles = vec![shot(start, 0, -1)];
+        run_idle(&mut state, 2 * ENEMY_SHOT_INTERVAL as usize);
+        assert_eq!(state.projectiles[0].pos.col, 0);
+        assert!(state.projectiles[0].pos.row > start);
+
+        // Straight shots keep to their column.
+        state.args.enemy_bullet_pattern = BulletPattern::Straight;
+        state.projectiles = vec![shot(start, 10, 0)];
+        run_idle(&mut state, 4 * ENEMY_SHOT_INTERVAL as usize);
+        assert_eq!(state.projectiles[0].pos, entities::Pos::new(start + 8, 10));
+    }
+
+    #[test]
+    fn a_cleared_wave_tallies_its_bonus_before_the_next_one_enters() {
+        let mut state = test_state(20, 40);
+        (state.args.sim_rate, state.lives, state.score) = (Some(10), 2, 120);
+        state.args.scoring.wave_bonus_per_life = 50;
+
+        state.enemies.clear();
+        state.tick(&[]);
+        assert_eq!(state.phase, Phase::WaveCleared(state.interlude_duration()));
+        assert_eq!((state.wave_bonus, state.score), (100, 220), "fifty for each of two lives left");
+        let frame = capture(&state).join("\n");
+        assert!(frame.contains("WAVE 1 CLEARED") && frame.contains("BONUS +100"), "{frame}");
+
+        // Nothing enters until the transition's over.
+        let interlude = state.interlude_duration() as usize;
+        run_idle(&mut state, interlude - 1);
+        assert!(matches!(state.phase, Phase::WaveCleared(_)));
+        assert!(state.enemies.is_empty());
+        assert_eq!(state.score, 220, "tallied once");
+
+        state.tick(&[]);
+        assert_eq!((state.phase, state.wave), (Phase::Playing, 2));
+        assert!(!state.enemies.is_empty());
+    }
+
+    #[test]
+    fn escapes_match_the_detected_or_chosen_color_depth() {
+        use colors::{Color, ColorDepth};
+        assert_eq!(ColorDepth::from_env("truecolor", "xterm-256color"), ColorDepth::TrueColor);
+        assert_eq!(ColorDepth::from_env("24bit", ""), ColorDepth::TrueColor);
+        assert_eq!(ColorDepth::from_env("", "xterm-direct"), ColorDepth::TrueColor);
+        assert_eq!(ColorDepth::from_env("", "screen-256color"), ColorDepth::Ansi256);
+        assert_eq!(ColorDepth::from_env("", "xterm"), ColorDepth::Ansi16);
+        assert_eq!(ColorDepth::from_env("", ""), ColorDepth::Ansi16, "the basic colors when unsure");
+
+        let orange = Color::rgb(255, 128, 0);
+        assert_eq!(orange.escape(ColorDepth::TrueColor), "\x1b[38;2;255;128;0m");
+        assert_eq!(orange.escape(ColorDepth::Ansi256), "\x1b[38;5;214m", "the closest in the color cube");
+        assert_eq!(orange.escape(ColorDepth::Ansi16), "\x1b[93m", "bright yellow");
+        assert_eq!(Color::rgb(0, 0, 150).escape(ColorDepth::Ansi16), "\x1b[34m", "plain blue");
+
+        // Overridden by flag (or variable), whatever was detected.
+        let resolve = |flags: &[&str], vars: &[(&str, &str)]| {
+            return Arguments::resolve(flags.iter().map(|flag| flag.to_string()), vars.iter().map(|(name, value)| (name.to_string(), value.to_string())));
+        };
+        assert_eq!(resolve(&["--color-depth", "256"], &[]).unwrap().color_depth, ColorDepth::Ansi256);
+        assert_eq!(resolve(&[], &[("CLI_NVADERS_COLOR_DEPTH", "16")]).unwrap().color_depth, ColorDepth::Ansi16);
+        assert_eq!(resolve(&["--color-depth", "24bit"], &[("CLI_NVADERS_COLOR_DEPTH", "16")]).unwrap().color_depth, ColorDepth::TrueColor);
+        assert!(resolve(&["--color-depth", "88"], &[]).is_err());
+
+        // And so is every colored cell of a frame.
+        let mut state = test_state(20, 40);
+        (state.args.no_color, state.args.color_depth) = (false, ColorDepth::Ansi256);
+        let frame = capture(&state).join("\n");
+        assert!(frame.contains("\x1b[38;5;") && !frame.contains("\x1b[38;2;"));
+    }
+
+    #[test]
+    fn the_formation_invades_at_the_offset_above_the_ship() {
+        let args = Arguments { seed: Some(7), no_color: true, hud_position: HudPosition::Bottom, invasion_row_offset: 4, max_enemy_projectiles: Some(0), ..Arguments::default() };
+        let mut state = GameState::new(args.clone());
+        state.reset(args, Size { rows: 20, cols: 40 });
+        state.phase = Phase::Playing;
+        let (ship, hud) = (state.player_row(), state.play_bottom());
+        assert_eq!(state.invasion_row(), ship - 4);
+
+        let mut lowest = 0;
+        for _ in 0..20_000 {
+            state.tick(&[]);
+            lowest = lowest.max(state.enemies.iter().map(|alien| alien.pos.row).max().unwrap_or(0));
+            if state.phase != Phase::Playing {
+                break;
+            }
+        }
+        assert_eq!(state.phase, Phase::GameOver, "invaded");
+        assert_eq!(lowest, ship - 4, "lost the moment the formation reached the offset");
+        assert!(lowest < hud && lowest < ship, "never anywhere near the ship (or the HUD below it)");
+
+        // However large the offset, the invasion row stays within the play area.
+        state.args.invasion_row_offset = 100;
+        assert_eq!(state.invasion_row(), state.play_top());
+    }
+
+    #[test]
+    fn a_practice_invasion_restarts_the_wave_rather_than_adding_to_it() {
+        let mut state = test_state(20, 40);
+        state.args.practice = true;
+        run_idle(&mut state, ENTRY_FRAMES as usize);
+        let (aliens, wave) = (state.enemies.len(), state.wave);
+
+        // Drive the formation down to the invasion row, mid-descent.
+        for invasion in 1..=3 {
+            let row = state.invasion_row();
+            let drop = row - state.enemies.iter().map(|alien| alien.pos.row).max().unwrap();
+            for alien in state.enemies.iter_mut() {
+                alien.pos.row += drop;
+                alien.y = alien.pos.row as f32;
+            }
+            state.descent = 2;
+            state.tick(&[]);
+
+            assert_eq!(state.enemies.len(), aliens, "invasion #{invasion} leaves a single, fresh, formation");
+            assert_eq!((state.wave, state.lives, state.phase), (wave, state.args.lives, Phase::Playing));
+            assert_eq!(state.descent, 0);
+            assert_eq!(state.formation, FormationPhase::EnteringFormation { frame: 0 });
+            assert!(state.enemies.iter().all(|alien| alien.pos.row == state.play_top()), "entering from the top again");
+            run_idle(&mut state, ENTRY_FRAMES as usize);
+        }
+    }
+
+    /** Invariants of the padding / replacing helpers, over arbitrary (ASCII) rows. */
+    mod properties {
+        use proptest::prelude::*;
+        use unicode_width::UnicodeWidthStr;
+
+        use super::render;
+
+        proptest! {
+            #[test]
+            fn right_pad_always_fills_the_row(content in "[ -~]{0,64}", length in 0_usize..64) {
+                prop_assert_eq!(render::right_pad(content, length).width(), length);
+            }
+
+            #[test]
+            fn replace_at_keeps_the_length(content in "[ -~]{0,64}", character in "[ -~]", index in 0_u16..80) {
+                let mut line = content.clone();
+                render::replace_at(&mut line, character.chars().next().unwrap(), index);
+                prop_assert_eq!(line.len(), content.len());
+            }
+
+            #[test]
+            fn left_pad_stays_within_the_row(content in "[ -~]{0,64}", start in 0_u16..80, length in 0_u16..80) {
+                let expected = if start >= length { length as usize } else { (start as usize + content.len()).min(length as usize) };
+                prop_assert_eq!(render::left_pad(start, content, length).width(), expected);
+            }
+        }
+    }
 }
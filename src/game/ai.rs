@@ -0,0 +1,63 @@
+/**
+ * Command-line Space Invaders, personal introduction to systems-level programming with Rust.
+ * @author Maxylan (https://github.com/Maxylan)
+ * @license MIT
+ */
+use super::{
+    entities::{Owner, Pos},
+    Action, EntityRef, GameState,
+};
+
+/** How many rows above the ship an enemy shot has to be before the AI bothers dodging it. */
+const DODGE_DISTANCE: u16 = 3;
+
+/**
+ * Decide what the player should do this frame, like the attract-mode demo of an old arcade cabinet.
+ * Moves the ship toward the column of the nearest alien and fires once its muzzle (see `GameState::muzzle_col`)
+ * is lined up beneath it, unless a shot is already on its way up that column. Incoming enemy shots are dodged first.
+ */
+pub fn decide(state: &GameState) -> Vec<Action> {
+    let ship = state.player();
+    let player = ship.pos.col;
+    let muzzle = state.muzzle_col();
+
+    // Would the ship, anchored at 'pos', be sitting under an incoming enemy shot?
+    let danger = |pos: u16| {
+        let rows = state.player_row().saturating_sub(DODGE_DISTANCE)..=state.player_row();
+        return rows.into_iter().any(|row| {
+            (pos..pos + ship.width).any(|col| match state.entity_at(Pos::new(row, col)) {
+                Some(EntityRef::Projectile(index)) => state.projectiles()[index].owner == Owner::Enemy,
+                _ => false,
+            })
+        });
+    };
+
+    if danger(player) {
+        // Step out from under the shot, toward whichever side is safe.
+        if player + ship.width < state.size.cols && !danger(player + 1) {
+            return vec![Action::MoveRight];
+        }
+
+        return vec![Action::MoveLeft];
+    }
+
+    let target = state
+        .enemies()
+        .iter()
+        .map(|alien| alien.center())
+        .min_by_key(|col| col.abs_diff(muzzle));
+
+    let mut actions: Vec<Action> = vec!();
+    match target {
+        Some(col) if col < muzzle && player > 0 && !danger(player - 1) => actions.push(Action::MoveLeft),
+        Some(col) if col > muzzle && !danger(player + 1) => actions.push(Action::MoveRight),
+        Some(col) if col != muzzle => {} // Wait for the shot in the way to pass (or give up on one out of reach).
+        Some(col) if !state.projectiles().iter().any(|projectile| projectile.owner == Owner::Player && projectile.pos.col == col) => {
+            actions.push(Action::Fire)
+        }
+        Some(_) => {}
+        None => {}
+    }
+
+    return actions;
+}
@@ -0,0 +1,85 @@
+/**
+ * Command-line Space Invaders, personal introduction to systems-level programming with Rust.
+ * @author Maxylan (https://github.com/Maxylan)
+ * @license MIT
+ */
+use std::{fmt, time};
+use termsize::Size;
+
+use super::{ai, Arguments, GameResult, GameState, Outcome, Phase};
+
+/** Terminal size the benchmark simulates, fixed so results are comparable between machines. */
+pub const BENCHMARK_SIZE: Size = Size { rows: 24, cols: 80 };
+
+/** Seed used when none is given, so two benchmark runs of the same build play the same game. */
+pub const BENCHMARK_SEED: u64 = 0;
+
+/** Aggregate stats of a benchmark run. */
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkStats {
+    pub frames: u32,
+    pub kills: u32,
+    /** Shots that hit something, shielded aliens (and UFOs) included, as `SessionStats.hits`. */
+    pub hits: u32,
+    pub shots_fired: u32,
+    pub score: u32,
+    pub wave: u16,
+    pub avg_step_time: time::Duration,
+}
+
+impl BenchmarkStats {
+    /** Percentage of fired shots that hit something, as `SessionStats::accuracy`. */
+    pub fn accuracy(&self) -> f32 {
+        if self.shots_fired == 0 {
+            return 0_f32;
+        }
+
+        return self.hits as f32 / self.shots_fired as f32 * 100_f32;
+    }
+}
+
+impl fmt::Display for BenchmarkStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Frames:      {}", self.frames)?;
+        writeln!(f, "Kills:       {}", self.kills)?;
+        writeln!(f, "Hits:        {}", self.hits)?;
+        writeln!(f, "Shots fired: {}", self.shots_fired)?;
+        writeln!(f, "Accuracy:    {:.1}%", self.accuracy())?;
+        writeln!(f, "Final score: {}", self.score)?;
+        writeln!(f, "Final wave:  {}", self.wave)?;
+        return write!(f, "Avg step:    {:?}", self.avg_step_time);
+    }
+}
+
+/**
+ * Run the headless simulation with the AI playing for `frames` steps (or until it loses),
+ * without touching the terminal. A benchmark is never lost, however the AI fared, so the outcome is always `Quit`.
+ */
+pub fn run(mut args: Arguments, frames: u32) -> (BenchmarkStats, Outcome) {
+    args.seed = Some(args.seed.unwrap_or(BENCHMARK_SEED));
+
+    let mut state = GameState::new(args.clone());
+    state.reset(args, BENCHMARK_SIZE);
+
+    let mut total = time::Duration::ZERO;
+    let mut steps = 0_u32;
+    while steps < frames && matches!(state.phase, Phase::Countdown(_) | Phase::Playing | Phase::WaveCleared(_)) {
+        let t = time::Instant::now();
+        let actions = ai::decide(&state);
+        state.tick(&actions);
+        total += t.elapsed();
+        steps += 1;
+    }
+
+    let report = BenchmarkStats {
+        frames: steps,
+        kills: state.stats.aliens_killed,
+        hits: state.stats.hits,
+        shots_fired: state.stats.shots_fired,
+        score: state.score,
+        wave: state.wave,
+        avg_step_time: total.checked_div(steps).unwrap_or_default(),
+    };
+
+    return (report, Outcome { result: GameResult::Quit, ..state.outcome() });
+}
@@ -0,0 +1,428 @@
+/**
+ * Command-line Space Invaders, personal introduction to systems-level programming with Rust.
+ * @author Maxylan (https://github.com/Maxylan)
+ * @license MIT
+ */
+use std::{fs, path::Path};
+use unicode_width::UnicodeWidthChar;
+
+use super::{colors::ColorDepth, glyphs::GlyphMap, input::InputMode, sprites::Skin, Arguments, BulletPattern, FireRule, FrameRate, HudPosition, MovementMode, RateDisplay, MAX_REPORTED_FPS};
+
+/** Prefix of the environment variables arguments may be set through, see `Arguments::with_env`. */
+pub const ENV_PREFIX: &str = "CLI_NVADERS_";
+
+impl Arguments {
+    /**
+     * Resolve the arguments in layers, each overriding the one before: the defaults, then the config file
+     * (see `with_file`, named by `--config` or `CLI_NVADERS_CONFIG`), then environment variables (see `with_env`),
+     * then command-line flags (without the leading program name).
+     * Returns a human-readable message describing the first bad flag (or variable, or line) encountered.
+     */
+    pub fn resolve<I, E>(flags: I, env: E) -> Result<Arguments, String>
+    where
+        I: IntoIterator<Item = String>,
+        E: IntoIterator<Item = (String, String)>,
+    {
+        let flags: Vec<String> = flags.into_iter().collect();
+        let env: Vec<(String, String)> = env.into_iter().collect();
+        let mut args = Arguments::default();
+
+        // The file sits below both the environment and the flags, yet may be named by either.
+        let named = Arguments::default().with_flags(flags.clone())?.config;
+        if let Some(path) = named.or(Arguments::default().with_env(env.clone())?.config) {
+            args = args.with_file(&path)?;
+        }
+
+        return args.with_env(env)?.with_flags(flags);
+    }
+    /**
+     * Apply every `CLI_NVADERS_<FLAG>` environment variable as its flag, e.g `CLI_NVADERS_FRAME_RATE=12` as
+     * `--frame-rate 12`. Switches (flags without a value) are set by `1` / `true` and left alone by `0` / `false`.
+     */
+    pub fn with_env<E>(mut self, env: E) -> Result<Arguments, String>
+    where
+        E: IntoIterator<Item = (String, String)>,
+    {
+        for (name, raw) in env {
+            let Some(key) = name.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+
+            let flag = format!("--{}", key.to_lowercase().replace('_', "-"));
+            self = self.layer(&flag, raw, &format!("'{name}'"))?;
+        }
+
+        return Ok(self);
+    }
+    /**
+     * Apply every `<flag> = <value>` line of the config file at `path` as its flag, e.g `frame_rate = 12` as
+     * `--frame-rate 12`. Switches are set (or left alone) as through `with_env`. Blank lines and `#` comments are skipped.
+     */
+    pub fn with_file(mut self, path: &Path) -> Result<Arguments, String> {
+        let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read the config file '{}', {e}", path.display()))?;
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let source = format!("line {} of '{}'", index + 1, path.display());
+            let Some((key, raw)) = line.split_once('=') else {
+                return Err(format!("Invalid {source}, expected '<flag> = <value>'"));
+            };
+            let flag = format!("--{}", key.trim().to_lowercase().replace('_', "-"));
+            self = self.layer(&flag, String::from(raw.trim()), &source)?;
+        }
+
+        return Ok(self);
+    }
+    /** Apply a flag set outside of the command line (by `source`), a switch being set by a truthy `raw` value. */
+    fn layer(self, flag: &str, raw: String, source: &str) -> Result<Arguments, String> {
+        let mut value = std::iter::once(raw.clone()).peekable();
+        let mut layered = self.clone();
+        layered.apply(flag, &mut value).map_err(|e| format!("{e} (set through {source})"))?;
+        if value.peek().is_none() {
+            return Ok(layered);
+        }
+
+        return match raw.to_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => Ok(layered),
+            "0" | "false" | "no" | "off" | "" => Ok(self),
+            other => Err(format!("Invalid value '{other}' for {source}, expected '1' or '0' ('{flag}' is a switch)")),
+        };
+    }
+    /** Parse command-line flags on top of whatever's been set already. */
+    pub fn with_flags<I>(mut self, flags: I) -> Result<Arguments, String>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut flags = flags.into_iter();
+        while let Some(flag) = flags.next() {
+            self.apply(&flag, &mut flags)?;
+        }
+
+        return Ok(self);
+    }
+    /** Apply a single flag, taking its value (if it has one) from `flags`. */
+    fn apply(&mut self, flag: &str, flags: &mut impl Iterator<Item = String>) -> Result<(), String> {
+        match flag {
+            "--hud" => {
+                self.hud_position = match value(flag, flags.next())?.as_str() {
+                    "top" => HudPosition::Top,
+                    "bottom" => HudPosition::Bottom,
+                    other => return Err(format!("Invalid value '{other}' for '{flag}', expected 'top' or 'bottom'")),
+                };
+            }
+            "--movement" => {
+                self.movement_mode = match value(flag, flags.next())?.as_str() {
+                    "clamp" => MovementMode::Clamp,
+                    "wrap" => MovementMode::Wrap,
+                    other => return Err(format!("Invalid value '{other}' for '{flag}', expected 'clamp' or 'wrap'")),
+                };
+            }
+            "--input" => {
+                self.input_mode = match value(flag, flags.next())?.as_str() {
+                    "queue" => InputMode::Queue,
+                    "last" => InputMode::LastWins,
+                    "cancel" => InputMode::Cancel,
+                    other => return Err(format!("Invalid value '{other}' for '{flag}', expected 'queue', 'last' or 'cancel'")),
+                };
+            }
+            "--input-capacity" => self.input_capacity = number(flag, flags.next())?,
+            "--hold-timeout" => self.hold_timeout = number(flag, flags.next())?,
+            "--frame-rate" | "--fps" => {
+                self.frame_rate = match value(flag, flags.next())?.as_str() {
+                    "uncapped" => FrameRate::Uncapped,
+                    raw => FrameRate::Capped(number(flag, Some(String::from(raw)))?),
+                };
+            }
+            "--ticker" => self.ticker = true,
+            "--hud-rate" => self.hud_rate = Some(number(flag, flags.next())?),
+            "--clean" => self.show_debug = false,
+            "--rates" => {
+                self.rate_display = match value(flag, flags.next())?.as_str() {
+                    "render" => RateDisplay::Render,
+                    "sim" => RateDisplay::Sim,
+                    "both" => RateDisplay::Both,
+                    other => return Err(format!("Invalid value '{other}' for '{flag}', expected 'render', 'sim' or 'both'")),
+                };
+            }
+            "--sim-rate" => self.sim_rate = Some(number(flag, flags.next())?),
+            "--enemy-time" => self.enemy_time = number(flag, flags.next())?,
+            "--min-enemy-time" => self.min_enemy_time = number(flag, flags.next())?,
+            "--mouse" => self.mouse = true,
+            "--gamepad" => self.gamepad = true,
+            "--fire-rule" => {
+                self.fire_rule = match value(flag, flags.next())?.as_str() {
+                    "front" => FireRule::FrontRow,
+                    "any" => FireRule::Any,
+                    other => return Err(format!("Invalid value '{other}' for '{flag}', expected 'front' or 'any'")),
+                };
+            }
+            "--force-size" => {
+                let raw = value(flag, flags.next())?;
+                let Some((cols, rows)) = raw.split_once('x') else {
+                    return Err(format!("Invalid value '{raw}' for '{flag}', expected '<cols>x<rows>'"));
+                };
+                self.force_size = Some((number(flag, Some(String::from(rows)))?, number(flag, Some(String::from(cols)))?));
+            }
+            "--min-rows" => self.min_rows = number(flag, flags.next())?,
+            "--min-cols" => self.min_cols = number(flag, flags.next())?,
+            "--arena-growth" => self.arena_growth = Some(number(flag, flags.next())?),
+            "--alien-skin" => {
+                let name = value(flag, flags.next())?;
+                self.alien_skin = match Skin::parse(&name) {
+                    Some(skin) => Some(skin),
+                    None => return Err(format!("Invalid value '{name}' for '{flag}', expected 'crabs', 'ufos' or 'letters'")),
+                };
+            }
+            "--no-stars" => self.stars = false,
+            "--background" => {
+                self.stars = match value(flag, flags.next())?.as_str() {
+                    "starry" => true,
+                    "blank" => false,
+                    other => return Err(format!("Invalid value '{other}' for '{flag}', expected 'starry' or 'blank'")),
+                };
+            }
+            "--background-char" => {
+                let raw = value(flag, flags.next())?;
+                let mut chars = raw.chars();
+                self.background_char = match (chars.next(), chars.next()) {
+                    (Some(character), None) => character,
+                    _ => return Err(format!("Invalid value '{raw}' for '{flag}', expected a single character")),
+                };
+            }
+            "--star-density" => self.star_density = number(flag, flags.next())?,
+            "--stars-while-paused" => self.stars_animate_while_paused = true,
+            "--invincible" => self.invincible = true,
+            "--profile" => self.profile = true,
+            "--crt" => self.crt = true,
+            "--safe-mode" => self.safe_mode = true,
+            "--set-title" => self.set_title = true,
+            "--max-enemy-shots" => {
+                self.max_enemy_projectiles = match value(flag, flags.next())?.as_str() {
+                    "unlimited" => None,
+                    raw => Some(number(flag, Some(String::from(raw)))?),
+                };
+            }
+            "--ufo-interval" => self.ufo_interval = number(flag, flags.next())?,
+            "--ufo-chance" => self.ufo_chance = number(flag, flags.next())?,
+            "--ufo-speed" => self.ufo_speed = number(flag, flags.next())?,
+            "--lives" => self.lives = number(flag, flags.next())?,
+            "--extra-life-every" => {
+                self.extra_life_every = match value(flag, flags.next())?.as_str() {
+                    "never" => None,
+                    raw => Some(number(flag, Some(String::from(raw)))?),
+                };
+            }
+            "--hardcore" => self.hardcore = true,
+            "--practice" => {
+                self.practice = true;
+                self.fixed_difficulty = true;
+                self.respawn_delay = self.respawn_delay.or(Some(PRACTICE_RESPAWN_DELAY));
+            }
+            "--respawn-delay" => self.respawn_delay = Some(number(flag, flags.next())?),
+            "--fixed-difficulty" => self.fixed_difficulty = true,
+            "--split-aliens" => self.split_aliens = true,
+            "--speed-growth" => self.difficulty.speed_growth = number(flag, flags.next())?,
+            "--fire-growth" => self.difficulty.fire_growth = number(flag, flags.next())?,
+            "--rows-every" => self.difficulty.rows_every = number(flag, flags.next())?,
+            "--max-rows" => self.difficulty.max_rows = number(flag, flags.next())?,
+            "--alien-points" => self.scoring.alien_points = number(flag, flags.next())?,
+            "--ufo-points" => self.scoring.ufo_points = number(flag, flags.next())?,
+            "--wave-bonus" => self.scoring.wave_bonus_per_life = number(flag, flags.next())?,
+            "--invasion-offset" => self.invasion_row_offset = number(flag, flags.next())?,
+            "--invasion-costs-life" => self.invasion_costs_life = true,
+            "--waves" => self.total_waves = Some(number(flag, flags.next())?),
+            "--time-attack" => self.time_limit = Some(number(flag, flags.next())?),
+            "--ascii" => self.ascii = true,
+            "--no-color" => self.no_color = true,
+            "--no-wave-colors" => self.wave_colors = false,
+            "--color-depth" => {
+                let name = value(flag, flags.next())?;
+                self.color_depth = match ColorDepth::parse(&name) {
+                    Some(depth) => depth,
+                    None => return Err(format!("Invalid value '{name}' for '{flag}', expected 'truecolor', '256' or '16'")),
+                };
+            }
+            "--glyph" => {
+                let raw = value(flag, flags.next())?;
+                match raw.split_once('=') {
+                    Some((name, glyph)) => {
+                        // Validated here for a helpful message, applied once the glyph set is detected.
+                        GlyphMap::ascii().set(name, glyph)?;
+                        self.glyphs.push((String::from(name), String::from(glyph)));
+                    }
+                    None => return Err(format!("Invalid value '{raw}' for '{flag}', expected '<name>=<glyph>'")),
+                }
+            }
+            "--demo" => self.demo = true,
+            "--benchmark" => self.benchmark = Some(number(flag, flags.next())?),
+            "--stats-log" => self.stats_log = Some(value(flag, flags.next())?.into()),
+            "--leaderboard" => self.leaderboard = Some(value(flag, flags.next())?.into()),
+            "--save" => self.save = Some(value(flag, flags.next())?.into()),
+            "--config" => self.config = Some(value(flag, flags.next())?.into()),
+            "--bullet-time" => self.bullet_time = number(flag, flags.next())?,
+            "--projectile-speed" => self.projectile_speed = number(flag, flags.next())?,
+            "--gravity" => self.gravity = number(flag, flags.next())?,
+            "--enemy-shot-speed" => self.enemy_projectile_speed = number(flag, flags.next())?,
+            "--enemy-shot-pattern" => {
+                self.enemy_bullet_pattern = match value(flag, flags.next())?.as_str() {
+                    "straight" => BulletPattern::Straight,
+                    "zigzag" => BulletPattern::ZigZag,
+                    other => return Err(format!("Invalid value '{other}' for '{flag}', expected 'straight' or 'zigzag'")),
+                };
+            }
+            "--fire-bias" => self.fire_bias = number(flag, flags.next())?,
+            "--seed" => self.seed = Some(number(flag, flags.next())?),
+            _ => return Err(format!("Unknown argument '{flag}'")),
+        }
+
+        return Ok(());
+    }
+    /**
+     * Reject nonsensical values, whether they came from flags or were set in code.
+     * Run before `start()`, so the game itself can take the arguments at face value.
+     */
+    pub fn validate(&self) -> Result<(), ArgError> {
+        let out_of_range = |name: &'static str, value: String, expected: &'static str| {
+            return Err(ArgError::OutOfRange { name, value, expected });
+        };
+
+        // No terminal redraws anywhere near as fast as the HUD can report, let alone faster.
+        match self.frame_rate {
+            FrameRate::Capped(rate) if rate == 0 || rate > MAX_REPORTED_FPS => {
+                return out_of_range("frame_rate", rate.to_string(), "1 to 999 frames per second, or uncapped");
+            }
+            _ => {}
+        }
+        if let Some(rate) = self.hud_rate.filter(|rate| *rate == 0) {
+            return out_of_range("hud_rate", rate.to_string(), "1 to 255 refreshes per second");
+        }
+        if let Some(rate) = self.sim_rate.filter(|rate| *rate == 0) {
+            return out_of_range("sim_rate", rate.to_string(), "1 to 255 steps per second");
+        }
+        if self.projectile_speed == 0 {
+            return out_of_range("projectile_speed", self.projectile_speed.to_string(), "at least 1 row per step");
+        }
+        if self.enemy_projectile_speed == 0 {
+            return out_of_range("enemy_projectile_speed", self.enemy_projectile_speed.to_string(), "at least 1 row per step");
+        }
+        if self.enemy_time == 0 {
+            return out_of_range("enemy_time", self.enemy_time.to_string(), "at least 1 frame");
+        }
+        if self.min_enemy_time == 0 || self.min_enemy_time > self.enemy_time {
+            return out_of_range("min_enemy_time", self.min_enemy_time.to_string(), "1 frame up to enemy_time");
+        }
+        if self.lives == 0 {
+            return out_of_range("lives", self.lives.to_string(), "at least 1");
+        }
+        if let Some(every) = self.extra_life_every.filter(|every| *every == 0) {
+            return out_of_range("extra_life_every", every.to_string(), "at least 1 point");
+        }
+        if !self.gravity.is_finite() || self.gravity < 0_f32 {
+            return out_of_range("gravity", self.gravity.to_string(), "zero or more");
+        }
+        if !self.difficulty.speed_growth.is_finite() || self.difficulty.speed_growth < 0_f32 {
+            return out_of_range("speed_growth", self.difficulty.speed_growth.to_string(), "zero or more");
+        }
+        if !self.difficulty.fire_growth.is_finite() || self.difficulty.fire_growth < 0_f32 {
+            return out_of_range("fire_growth", self.difficulty.fire_growth.to_string(), "zero or more");
+        }
+        if self.difficulty.max_rows == 0 {
+            return out_of_range("max_rows", self.difficulty.max_rows.to_string(), "at least 1 row");
+        }
+        if self.invasion_row_offset >= self.min_rows / 2 {
+            return out_of_range("invasion_row_offset", self.invasion_row_offset.to_string(), "fewer rows than half of min_rows");
+        }
+        if self.min_rows < MIN_ROWS {
+            return out_of_range("min_rows", self.min_rows.to_string(), "at least 10 rows");
+        }
+        if self.min_cols < MIN_COLS {
+            return out_of_range("min_cols", self.min_cols.to_string(), "at least 20 columns");
+        }
+        if let Some(every) = self.arena_growth.filter(|every| *every == 0) {
+            return out_of_range("arena_growth", every.to_string(), "at least 1 point");
+        }
+        if let Some(seconds) = self.time_limit.filter(|seconds| *seconds == 0) {
+            return out_of_range("time_limit", seconds.to_string(), "at least 1 second");
+        }
+        if let Some(waves) = self.total_waves.filter(|waves| *waves == 0) {
+            return out_of_range("total_waves", waves.to_string(), "at least 1 wave");
+        }
+        if !self.fire_bias.is_finite() || self.fire_bias < 0_f32 {
+            return out_of_range("fire_bias", self.fire_bias.to_string(), "zero or more");
+        }
+        if !(0_f32..=1000_f32).contains(&self.star_density) {
+            return out_of_range("star_density", self.star_density.to_string(), "0 to 1000 stars per thousand cells");
+        }
+        if self.background_char.is_control() || self.background_char.width() != Some(1) {
+            return out_of_range("background_char", format!("{:?}", self.background_char), "a single printable, single-cell character");
+        }
+        if self.gamepad && !cfg!(feature = "gamepad") {
+            return out_of_range("gamepad", self.gamepad.to_string(), "false, this build has no gamepad support (feature 'gamepad')");
+        }
+        if self.input_capacity == 0 {
+            return out_of_range("input_capacity", self.input_capacity.to_string(), "at least 1 action");
+        }
+        if self.hold_timeout == 0 {
+            return out_of_range("hold_timeout", self.hold_timeout.to_string(), "at least 1 millisecond");
+        }
+        if let Some(delay) = self.respawn_delay.filter(|delay| *delay == 0) {
+            return out_of_range("respawn_delay", delay.to_string(), "at least 1 step");
+        }
+        if !(0_f32..=1_f32).contains(&self.ufo_chance) {
+            return out_of_range("ufo_chance", self.ufo_chance.to_string(), "0.0 to 1.0");
+        }
+        if self.ufo_speed == 0 {
+            return out_of_range("ufo_speed", self.ufo_speed.to_string(), "at least 1 column per step");
+        }
+        if let Some(frames) = self.benchmark.filter(|frames| *frames == 0) {
+            return out_of_range("benchmark", frames.to_string(), "at least 1 frame");
+        }
+
+        return Ok(());
+    }
+}
+
+/** Steps destroyed aliens take to respawn in practice (`--practice`), unless told otherwise. */
+const PRACTICE_RESPAWN_DELAY: u16 = 24;
+/** Smallest `min_rows` accepted, anything less can't fit the HUD, a formation and the player. */
+pub const MIN_ROWS: u16 = 10;
+/** Smallest `min_cols` accepted. */
+pub const MIN_COLS: u16 = 20;
+
+/** An argument holding a value the game can't (sensibly) run with. */
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgError {
+    OutOfRange {
+        /** Name of the offending `Arguments` field. */
+        name: &'static str,
+        value: String,
+        /** Human-readable description of the accepted values. */
+        expected: &'static str,
+    },
+}
+
+impl std::fmt::Display for ArgError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            ArgError::OutOfRange { name, value, expected } => {
+                write!(f, "Invalid value '{value}' for '{name}', expected {expected}")
+            }
+        };
+    }
+}
+
+/** Unwrap the value following a flag, or complain that it's missing. */
+fn value(flag: &str, value: Option<String>) -> Result<String, String> {
+    return value.ok_or(format!("Missing value for '{flag}'"));
+}
+
+/** Parse the (numeric) value following a flag. */
+fn number<T: std::str::FromStr>(flag: &str, next: Option<String>) -> Result<T, String> {
+    let raw = value(flag, next)?;
+    return raw
+        .parse::<T>()
+        .map_err(|_| format!("Invalid value '{raw}' for '{flag}', expected a number"));
+}
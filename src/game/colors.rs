@@ -0,0 +1,221 @@
+/**
+ * Command-line Space Invaders, personal introduction to systems-level programming with Rust.
+ * @author Maxylan (https://github.com/Maxylan)
+ * @license MIT
+ */
+use std::{env, fmt::Write as _};
+
+/** How many colors the terminal can display, escapes degrading to the closest color available. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDepth {
+    /** 24-bit RGB. */
+    TrueColor,
+    /** The 256-color palette. */
+    Ansi256,
+    /** The 16 basic (and bright) colors, understood by pretty much every terminal. */
+    Ansi16,
+}
+
+impl ColorDepth {
+    /** Detect the terminal's color depth from `COLORTERM` and `TERM`, assuming the 16 basic colors when unsure. */
+    pub fn detect() -> Self {
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        let term = env::var("TERM").unwrap_or_default();
+        return ColorDepth::from_env(&colorterm, &term);
+    }
+    /** Color depth of a terminal with the given `COLORTERM` and `TERM` values. */
+    pub fn from_env(colorterm: &str, term: &str) -> Self {
+        if matches!(colorterm, "truecolor" | "24bit") || term.ends_with("-direct") {
+            return ColorDepth::TrueColor;
+        }
+        if term.contains("256color") {
+            return ColorDepth::Ansi256;
+        }
+
+        return ColorDepth::Ansi16;
+    }
+    pub fn parse(name: &str) -> Option<ColorDepth> {
+        return match name {
+            "truecolor" | "24bit" => Some(ColorDepth::TrueColor),
+            "256" => Some(ColorDepth::Ansi256),
+            "16" => Some(ColorDepth::Ansi16),
+            _ => None,
+        };
+    }
+}
+
+/** A 24-bit (truecolor) RGB color. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        return Color { r, g, b };
+    }
+    /** Linearly interpolate toward `other`, `t` being clamped to `0.0..=1.0`. */
+    pub fn lerp(self, other: Color, t: f32) -> Color {
+        let t = t.clamp(0_f32, 1_f32);
+        let channel = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * t).round() as u8;
+        return Color::rgb(channel(self.r, other.r), channel(self.g, other.g), channel(self.b, other.b));
+    }
+    /**
+     * Rotate the hue by `degrees`, keeping (approximately) the same luminance.
+     * Uses the same matrix as CSS' `hue-rotate()` filter.
+     */
+    pub fn hue_rotate(self, degrees: f32) -> Color {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let (r, g, b) = (self.r as f32, self.g as f32, self.b as f32);
+        let channel = |value: f32| value.round().clamp(0_f32, 255_f32) as u8;
+        return Color::rgb(
+            channel(r * (0.213 + cos * 0.787 - sin * 0.213) + g * (0.715 - cos * 0.715 - sin * 0.715) + b * (0.072 - cos * 0.072 + sin * 0.928)),
+            channel(r * (0.213 - cos * 0.213 + sin * 0.143) + g * (0.715 + cos * 0.285 + sin * 0.140) + b * (0.072 - cos * 0.072 - sin * 0.283)),
+            channel(r * (0.213 - cos * 0.213 - sin * 0.787) + g * (0.715 - cos * 0.715 + sin * 0.715) + b * (0.072 + cos * 0.928 + sin * 0.072)),
+        );
+    }
+    /** ANSI escape setting this as the foreground color, as close as the terminal's color `depth` gets. */
+    #[cfg(test)]
+    pub fn escape(&self, depth: ColorDepth) -> String {
+        let mut escape = String::new();
+        self.write_escape(depth, &mut escape);
+        return escape;
+    }
+    /** Write the escape setting this as the foreground color (see `escape`) onto `out`, without allocating one of its own. */
+    pub fn write_escape(&self, depth: ColorDepth, out: &mut String) {
+        let _ = match depth {
+            ColorDepth::TrueColor => write!(out, "\x1b[38;2;{};{};{}m", self.r, self.g, self.b),
+            ColorDepth::Ansi256 => {
+                // The 6x6x6 color cube of the 256-color palette.
+                let level = |channel: u8| (channel as u16 * 5 + 127) / 255;
+                write!(out, "\x1b[38;5;{}m", 16 + 36 * level(self.r) + 6 * level(self.g) + level(self.b))
+            }
+            ColorDepth::Ansi16 => {
+                // Each channel either on or off, the bright variant when any channel is strong.
+                let bit = |channel: u8, shift: u8| ((channel > 127) as u8) << shift;
+                let index = bit(self.r, 0) | bit(self.g, 1) | bit(self.b, 2);
+                let base = if self.r.max(self.g).max(self.b) > 191 { 90 } else { 30 };
+                write!(out, "\x1b[{}m", base + index)
+            }
+        };
+    }
+}
+
+/** ANSI escape resetting all colors/attributes. */
+pub const RESET: &str = "\x1b[0m";
+/** Dims whatever follows, until `UNDIM` (or a `RESET`). */
+pub const DIM: &str = "\x1b[2m";
+/** Ends a `DIM`, leaving colors alone. */
+pub const UNDIM: &str = "\x1b[22m";
+
+/** Color of aliens far away from the player, at the top of the play area. */
+pub const ALIEN_SAFE: Color = Color::rgb(80, 220, 100);
+/** Color of aliens about to reach the player. */
+pub const ALIEN_DANGER: Color = Color::rgb(255, 40, 40);
+
+/** Degrees the hue of the aliens (and the UFO) rotates by with every wave, see `wave_shift`. */
+const WAVE_HUE_STEP: f32 = 40_f32;
+
+/** Color of the bonus UFO. */
+pub const UFO: Color = Color::rgb(255, 80, 255);
+
+/** Color of the inspector's cursor. */
+pub const CURSOR: Color = Color::rgb(0, 200, 255);
+/** Color of a fresh score popup. */
+pub const POPUP: Color = Color::rgb(255, 255, 120);
+/** Color a score popup fades toward, as it runs out. */
+pub const POPUP_FADED: Color = Color::rgb(90, 90, 90);
+
+/** Colors of everything tinted in the play area (bar the inspector's cursor), cycled through at runtime. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub name: &'static str,
+    /** Aliens far away from the player, at the top of the play area. */
+    pub alien_safe: Color,
+    /** Aliens about to reach the player. */
+    pub alien_danger: Color,
+    pub ufo: Color,
+    /** A fresh score popup, fading toward `popup_faded` as it runs out. */
+    pub popup: Color,
+    pub popup_faded: Color,
+}
+
+/** Every theme, in the order they're cycled through (`t`). The first is the default. */
+pub const THEMES: [Theme; 4] = [
+    Theme { name: "Classic", alien_safe: ALIEN_SAFE, alien_danger: ALIEN_DANGER, ufo: UFO, popup: POPUP, popup_faded: POPUP_FADED },
+    Theme {
+        name: "Ocean",
+        alien_safe: Color::rgb(80, 200, 255),
+        alien_danger: Color::rgb(255, 120, 40),
+        ufo: Color::rgb(255, 220, 80),
+        popup: Color::rgb(180, 255, 255),
+        popup_faded: Color::rgb(60, 80, 100),
+    },
+    Theme {
+        name: "Amber",
+        alien_safe: Color::rgb(255, 176, 0),
+        alien_danger: Color::rgb(255, 60, 0),
+        ufo: Color::rgb(255, 220, 120),
+        popup: Color::rgb(255, 230, 160),
+        popup_faded: Color::rgb(90, 60, 20),
+    },
+    Theme {
+        name: "Mono",
+        alien_safe: Color::rgb(150, 150, 150),
+        alien_danger: Color::rgb(255, 255, 255),
+        ufo: Color::rgb(200, 200, 200),
+        popup: Color::rgb(255, 255, 255),
+        popup_faded: Color::rgb(80, 80, 80),
+    },
+];
+
+impl Theme {
+    /**
+     * Tint for an alien on `row`, shifting from `alien_safe` at the top of the play area (`top`)
+     * to `alien_danger` as it approaches the player (`player_row`).
+     */
+    pub fn alien_color(&self, row: u16, top: u16, player_row: u16) -> Color {
+        let span = player_row.saturating_sub(top).max(1);
+        let progress = row.saturating_sub(top) as f32 / span as f32;
+        return self.alien_safe.lerp(self.alien_danger, progress);
+    }
+}
+
+/**
+ * Hue offset (in degrees) of `wave`'s palette, so later waves look distinct from earlier ones.
+ * None for the first wave, cycling back around after nine.
+ */
+pub fn wave_shift(wave: u16) -> f32 {
+    return (wave.saturating_sub(1) as f32 * WAVE_HUE_STEP) % 360_f32;
+}
+
+/** Whether the user opted out of colors through the `NO_COLOR` convention (https://no-color.org). */
+pub fn no_color_env() -> bool {
+    return env::var("NO_COLOR").is_ok_and(|value| !value.is_empty());
+}
+
+/**
+ * Write each cell of a row (its glyph and color, `None` leaving it uncolored) onto `painted`, wrapped in the escape
+ * of its color at the given color `depth`, only emitting escapes where the color changes.
+ */
+pub fn paint(cells: impl Iterator<Item = (char, Option<Color>)>, depth: ColorDepth, painted: &mut String) {
+    let mut current: Option<Color> = None;
+    for (character, color) in cells {
+        if color != current {
+            match color {
+                Some(color) => color.write_escape(depth, painted),
+                None => painted.push_str(RESET),
+            }
+
+            current = color;
+        }
+
+        painted.push(character);
+    }
+
+    if current.is_some() {
+        painted.push_str(RESET);
+    }
+}
@@ -0,0 +1,72 @@
+/**
+ * Command-line Space Invaders, personal introduction to systems-level programming with Rust.
+ * @author Maxylan (https://github.com/Maxylan)
+ * @license MIT
+ */
+use serde::{Deserialize, Serialize};
+
+/** Formation rows of the first wave. */
+const BASE_ROWS: u16 = 3;
+
+/**
+ * How the game ramps up from one wave to the next, mostly for the endless mode.
+ * Every wave past the first the formation marches (and fires) a little faster, and every
+ * `rows_every` waves it grows by a row, up to `max_rows`.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DifficultyCurve {
+    /** How much faster the formation marches with every wave, `0.1` being 10% of the first wave's pace. */
+    pub speed_growth: f32,
+    /** How much more often aliens fire with every wave, likewise. */
+    pub fire_growth: f32,
+    /** Waves between two extra formation rows, `0` to never add any. */
+    pub rows_every: u16,
+    /** Most formation rows a wave may have. */
+    pub max_rows: u16,
+}
+
+impl Default for DifficultyCurve {
+    fn default() -> Self {
+        return DifficultyCurve {
+            speed_growth: 0.1,
+            fire_growth: 0.1,
+            rows_every: 3,
+            max_rows: 5,
+        };
+    }
+}
+
+/** Parameters of a single wave, as set by the `DifficultyCurve`. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WaveParams {
+    /** Frames between two marches of the formation. */
+    pub march_interval: u8,
+    /** Frames between two enemy fire attempts. */
+    pub fire_interval: u8,
+    /** Rows of aliens in the formation. */
+    pub rows: u16,
+}
+
+impl DifficultyCurve {
+    /**
+     * Parameters of `wave` (counting from `1`), starting out from `base_interval` frames (`enemy_time`)
+     * between marches / fire attempts. Intervals shrink by their growth factor, never below a frame.
+     */
+    pub fn at(&self, wave: u16, base_interval: u8) -> WaveParams {
+        let waves = wave.saturating_sub(1);
+        let interval = |growth: f32| {
+            let scaled = base_interval.max(1) as f32 / (1_f32 + growth.max(0_f32) * waves as f32);
+            return scaled.round().clamp(1_f32, base_interval.max(1) as f32) as u8;
+        };
+        let extra_rows = match self.rows_every {
+            0 => 0,
+            every => waves / every,
+        };
+
+        return WaveParams {
+            march_interval: interval(self.speed_growth),
+            fire_interval: interval(self.fire_growth),
+            rows: BASE_ROWS.saturating_add(extra_rows).min(self.max_rows).max(1),
+        };
+    }
+}
@@ -3,20 +3,267 @@
  * @author Maxylan (https://github.com/Maxylan)
  * @license MIT
  */
+use serde::{Deserialize, Serialize};
+use termsize::Size;
+
+use super::MovementMode;
+
+/** A cell on screen, rows counting down from the top and columns right from the left edge (both from `0`). */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Pos {
+    pub row: u16,
+    pub col: u16,
+}
+
+impl Pos {
+    pub fn new(row: u16, col: u16) -> Self {
+        return Pos { row, col };
+    }
+    /** Whether the cell lies within a screen (or arena) of the given size. */
+    pub fn in_bounds(&self, size: &Size) -> bool {
+        return self.row < size.rows && self.col < size.cols;
+    }
+    /** The nearest cell within a screen (or arena) of the given size, which mustn't be empty. */
+    pub fn clamped(&self, size: &Size) -> Pos {
+        return Pos {
+            row: self.row.min(size.rows.saturating_sub(1)),
+            col: self.col.min(size.cols.saturating_sub(1)),
+        };
+    }
+}
+
+/** A block of cells, `w` columns by `h` rows, its top-left cell at `row`,`col`. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub row: u16,
+    pub col: u16,
+    pub w: u16,
+    pub h: u16,
+}
+
+impl Rect {
+    /** Whether the given cell lies within the block. */
+    pub fn contains(&self, row: u16, col: u16) -> bool {
+        return row >= self.row && row < self.row + self.h && col >= self.col && col < self.col + self.w;
+    }
+    /** Whether the blocks share any column, whatever their rows. */
+    pub fn shares_cols(&self, other: &Rect) -> bool {
+        return self.col < other.col + other.w && other.col < self.col + self.w;
+    }
+    /** Whether the blocks share any cell. */
+    pub fn intersects(&self, other: &Rect) -> bool {
+        return self.shares_cols(other) && self.row < other.row + other.h && other.row < self.row + self.h;
+    }
+    /** Columns the block covers, cut off at `cols` (for indexing into a row of that many cells). */
+    pub fn span(&self, cols: usize) -> std::ops::Range<usize> {
+        return (self.col as usize).min(cols)..((self.col + self.w) as usize).min(cols);
+    }
+    /** The block's column, pulled back so the whole block fits within `cols`. */
+    pub fn clamped_col(&self, cols: u16) -> u16 {
+        return self.col.min(cols.saturating_sub(self.w));
+    }
+}
+
+/**
+ * Anything taking up cells on screen. Its `bounds` are the one footprint collisions, clamping
+ * and rendering all go by, so a multi-cell sprite is never treated as a single cell.
+ */
+pub trait Bounded {
+    fn bounds(&self) -> Rect;
+}
+
 #[derive(Debug)]
 pub struct Player {
-    pub pos: u16,
+    /** Left-most cell of the ship's sprite, its row being the player's row (see `GameState::player_row`). */
+    pub pos: Pos,
+    /** Number of cells the sprite spans, starting at `pos`. */
+    pub width: u16,
+}
+
+impl Player {
+    /**
+     * Move the player one column to the left, stopping at the first column.
+     * When wrapping, the ship instead reappears with its right edge on the last column.
+     */
+    pub fn move_left(&mut self, cols: u16, mode: MovementMode) {
+        if self.pos.col > 0 {
+            self.pos.col -= 1;
+        } else if mode == MovementMode::Wrap {
+            self.pos.col = cols.saturating_sub(self.width);
+        }
+    }
+    /**
+     * Move the player one column to the right, stopping once the sprite's right edge reaches the last column (`cols - 1`).
+     * When wrapping, the ship instead reappears (fully) on the first column.
+     */
+    pub fn move_right(&mut self, cols: u16, mode: MovementMode) {
+        if self.pos.col + self.width < cols {
+            self.pos.col += 1;
+        } else if mode == MovementMode::Wrap {
+            self.pos.col = 0;
+        }
+    }
+    /** Whether any cell of the ship's sprite covers the given column. */
+    pub fn occupies(&self, col: u16) -> bool {
+        return self.bounds().contains(self.pos.row, col);
+    }
+    /** Column at the center of the sprite. */
+    pub fn center(&self) -> u16 {
+        return self.pos.col + (self.width >> 1);
+    }
+    /** Pull the ship back on screen, should its sprite stick out past the last column. */
+    pub fn clamp(&mut self, cols: u16) {
+        self.pos.col = self.bounds().clamped_col(cols);
+    }
+}
+
+impl Bounded for Player {
+    fn bounds(&self) -> Rect {
+        return Rect { row: self.pos.row, col: self.pos.col, w: self.width, h: 1 };
+    }
 }
 
 #[derive(Debug)]
 pub struct FallingStar {
-    pub pos: u16,
-    pub col: u16,
+    pub pos: Pos,
     pub entity: char,
 }
 
-#[derive(Debug)]
-pub struct Projectile {}
+/** Floating "+points" text left behind by a kill, drifting up and fading until `frames_left` runs out. */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScorePopup {
+    pub value: u32,
+    /** Cell the text is centered on. */
+    pub pos: Pos,
+    pub frames_left: u16,
+}
 
-#[derive(Debug)]
-pub struct Alien {}
+impl ScorePopup {
+    pub fn text(&self) -> String {
+        return format!("+{}", self.value);
+    }
+}
+
+/** Who fired a projectile, player shots travel up and hit aliens, enemy shots travel down and hit the player. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Owner {
+    Player,
+    Enemy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Projectile {
+    pub pos: Pos,
+    /** Row the projectile was on before its latest step. */
+    pub from_row: u16,
+    pub owner: Owner,
+    /** Column the projectile sidesteps by on its next step, for zig-zagging shots (see `BulletPattern`). */
+    pub drift: i8,
+    /** Exact row, between cells, as the projectile arcs under `gravity` (`pos.row` being it rounded). */
+    pub y: f32,
+    /** Rows travelled per step under `gravity`, negative while rising. */
+    pub velocity: f32,
+}
+
+impl Projectile {
+    /** Every row passed through during the latest step, in the order they were travelled (`from_row` to `row`). */
+    pub fn path(&self) -> Vec<u16> {
+        if self.from_row >= self.pos.row {
+            return (self.pos.row..=self.from_row).rev().collect();
+        }
+
+        return (self.from_row..=self.pos.row).collect();
+    }
+    /** Cells passed through during the latest step (see `path`), as a block. */
+    pub fn swept(&self) -> Rect {
+        let top = self.from_row.min(self.pos.row);
+        return Rect { row: top, col: self.pos.col, w: 1, h: self.from_row.max(self.pos.row) - top + 1 };
+    }
+}
+
+impl Bounded for Projectile {
+    fn bounds(&self) -> Rect {
+        return Rect { row: self.pos.row, col: self.pos.col, w: 1, h: 1 };
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alien {
+    /** Stable identifier, unique within a game (counting up from `1` as aliens spawn), wherever the alien moves in `enemies`. */
+    pub id: u32,
+    /** Left-most cell of the sprite, its row (collisions included) being `y` rounded. */
+    pub pos: Pos,
+    /** Fractional row, only ever between two rows while the formation descends. */
+    pub y: f32,
+    /** Number of cells the sprite spans, starting at `col`. */
+    pub width: u16,
+    /** Row of the alien's place in formation, moved toward while the formation is entering. */
+    pub target_row: u16,
+    /** Row of the formation the alien spawned in, `0` being the back row. */
+    pub rank: u16,
+    /** Hits left before the alien is destroyed, shielded aliens start with more than one. */
+    pub hp: u8,
+    /** Size class, aliens above tier `1` split into two fragments a tier lower when destroyed. */
+    pub tier: u8,
+    /** Spawned by a splitting alien, a single cell wide. */
+    pub fragment: bool,
+}
+
+impl Alien {
+    /** Whether any cell of the alien's sprite covers the given cell. */
+    pub fn occupies(&self, row: u16, col: u16) -> bool {
+        return self.bounds().contains(row, col);
+    }
+    /** Column at the center of the sprite. */
+    pub fn center(&self) -> u16 {
+        return self.pos.col + (self.width >> 1);
+    }
+    /** Whether the alien can still take more than one hit. */
+    pub fn is_shielded(&self) -> bool {
+        return self.hp > 1;
+    }
+}
+
+impl Bounded for Alien {
+    fn bounds(&self) -> Rect {
+        return Rect { row: self.pos.row, col: self.pos.col, w: self.width, h: 1 };
+    }
+}
+
+/** The bonus UFO, flying across the top row of the play area. */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ufo {
+    /** Left-most cell of the sprite. */
+    pub pos: Pos,
+    /** Left-most column before its latest step, so a UFO flying several columns a step can't skip over shots (see `swept`). */
+    pub from_col: u16,
+    /** Number of cells the sprite spans, starting at `col`. */
+    pub width: u16,
+    /** `1` flying right, `-1` flying left. */
+    pub direction: i8,
+}
+
+impl Ufo {
+    /** Whether any cell of the UFO's sprite covers the given cell. */
+    pub fn occupies(&self, row: u16, col: u16) -> bool {
+        return self.bounds().contains(row, col);
+    }
+    /**
+     * Cells the sprite passed over during the latest step, as a block. Every column it stood at after
+     * leaving `from_col` up to `col`, so a UFO flying a column a step covers exactly its `bounds`.
+     */
+    pub fn swept(&self) -> Rect {
+        let (left, right) = match self.pos.col.cmp(&self.from_col) {
+            std::cmp::Ordering::Greater => (self.from_col + 1, self.pos.col),
+            std::cmp::Ordering::Less => (self.pos.col, self.from_col - 1),
+            std::cmp::Ordering::Equal => (self.pos.col, self.pos.col),
+        };
+        return Rect { row: self.pos.row, col: left, w: right - left + self.width, h: 1 };
+    }
+}
+
+impl Bounded for Ufo {
+    fn bounds(&self) -> Rect {
+        return Rect { row: self.pos.row, col: self.pos.col, w: self.width, h: 1 };
+    }
+}
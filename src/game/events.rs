@@ -0,0 +1,53 @@
+/**
+ * Command-line Space Invaders, personal introduction to systems-level programming with Rust.
+ * @author Maxylan (https://github.com/Maxylan)
+ * @license MIT
+ */
+/**
+ * Notable things that happened during a tick. Emitted through `GameState::emit`,
+ * the events of the latest tick are kept on the state for anything that wants to react to them.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /** The player fired a projectile. */
+    ShotFired,
+    /** A player projectile left the play area without hitting anything. */
+    ShotMissed,
+    /** Alien `id` (see `Alien::id`) was destroyed at the given (anchor) cell, awarding `points`. */
+    AlienDestroyed { id: u32, row: u16, col: u16, points: u32 },
+    /** A player projectile cracked shielded alien `id`, without destroying it. */
+    AlienCracked { id: u32, row: u16, col: u16 },
+    /** A player projectile shot down the bonus UFO. */
+    UfoDestroyed { row: u16, col: u16, points: u32 },
+    /** Every alien of the given wave was destroyed, awarding a `bonus` for the lives left. */
+    WaveCleared { wave: u16, bonus: u32 },
+    /** An enemy projectile hit the player's ship, costing a life. */
+    PlayerHit,
+    /** The player was awarded an extra life. */
+    ExtraLife,
+    /** A game was placed on the leaderboard, at `place` (`0` being best). */
+    HighScore { place: usize },
+    /** The game was lost. */
+    GameOver,
+    /** The final wave of a finite campaign was cleared. */
+    CampaignWon,
+}
+
+impl Event {
+    /** Line the event makes on the HUD's ticker (see `Arguments.ticker`), if it's notable enough. */
+    pub fn headline(&self) -> Option<String> {
+        return match self {
+            Event::AlienDestroyed { .. } => Some(String::from("Alien down!")),
+            Event::UfoDestroyed { points, .. } => Some(format!("UFO +{points}!")),
+            Event::WaveCleared { wave, .. } => Some(format!("Wave {wave} cleared!")),
+            Event::PlayerHit => Some(String::from("Life lost!")),
+            Event::ExtraLife => Some(String::from("Extra life!")),
+            Event::ShotFired
+            | Event::ShotMissed
+            | Event::AlienCracked { .. }
+            | Event::HighScore { .. }
+            | Event::GameOver
+            | Event::CampaignWon => None,
+        };
+    }
+}
@@ -0,0 +1,160 @@
+/**
+ * Command-line Space Invaders, personal introduction to systems-level programming with Rust.
+ * @author Maxylan (https://github.com/Maxylan)
+ * @license MIT
+ */
+use std::time;
+
+use super::{
+    input::{HeldKeys, InputQueue, Keymap},
+    Action,
+};
+
+/** How far the stick has to be pushed (out of `1.0`) before it moves the ship, so a resting stick's drift doesn't. */
+const STICK_DEADZONE: f32 = 0.35;
+
+/** The few gamepad buttons the game uses. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PadButton {
+    Up,
+    Down,
+    Left,
+    Right,
+    /** The bottom face button (`A` / cross). */
+    South,
+    Start,
+}
+
+/** What a gamepad reported, whichever library it came through (see `Pads`). */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PadEvent {
+    Pressed(PadButton),
+    Released(PadButton),
+    /** The left stick moved horizontally, from `-1.0` (left) to `1.0` (right). */
+    StickX(f32),
+}
+
+/** Map a gamepad button to the action it's bound to, like `input::map_key` does keys. */
+pub fn map_button(button: PadButton, keymap: Keymap) -> Option<Action> {
+    return match (keymap, button) {
+        (Keymap::Game, PadButton::Left) => Some(Action::MoveLeft),
+        (Keymap::Game, PadButton::Right) => Some(Action::MoveRight),
+        (Keymap::Game, PadButton::South) => Some(Action::Fire),
+        (Keymap::Game | Keymap::Menu, PadButton::Start) => Some(Action::Pause),
+        (Keymap::Menu, PadButton::Up) => Some(Action::MenuUp),
+        (Keymap::Menu, PadButton::Down) => Some(Action::MenuDown),
+        (Keymap::Menu | Keymap::Text, PadButton::South) => Some(Action::Confirm),
+        _ => None,
+    };
+}
+
+/** Direction the stick is pushed in, if it's pushed past the deadzone. */
+fn stick_direction(x: f32) -> Option<Action> {
+    if x <= -STICK_DEADZONE {
+        return Some(Action::MoveLeft);
+    }
+    if x >= STICK_DEADZONE {
+        return Some(Action::MoveRight);
+    }
+
+    return None;
+}
+
+/**
+ * Input from gamepads, turned into the same actions as the keyboard's. Directions (d-pad or stick) are
+ * held like movement keys are (see `HeldKeys`), everything else is queued up along with the keyboard's actions.
+ */
+#[derive(Debug)]
+pub struct Gamepad {
+    held: HeldKeys,
+    /** Direction the stick was last pushed in. */
+    stick: Option<Action>,
+}
+
+impl Default for Gamepad {
+    fn default() -> Self {
+        // Gamepads always report releases.
+        return Gamepad { held: HeldKeys::new(true), stick: None };
+    }
+}
+
+impl Gamepad {
+    /** Handle a single gamepad event at `now`, queueing up whatever action it maps to on `buffer`. */
+    pub fn handle(&mut self, event: PadEvent, keymap: Keymap, buffer: &mut InputQueue, now: time::Instant) {
+        let is_movement = |action: &Action| matches!(action, Action::MoveLeft | Action::MoveRight);
+        match event {
+            PadEvent::Pressed(button) => match map_button(button, keymap) {
+                Some(direction) if is_movement(&direction) => self.held.press(direction, now),
+                Some(action) => buffer.push(action),
+                None => {}
+            },
+            PadEvent::Released(button) => {
+                if let Some(direction) = map_button(button, Keymap::Game).filter(is_movement) {
+                    self.held.release(direction);
+                }
+            }
+            PadEvent::StickX(x) => {
+                let direction = stick_direction(x).filter(|_| keymap == Keymap::Game);
+                if direction == self.stick {
+                    return;
+                }
+
+                if let Some(previous) = self.stick {
+                    self.held.release(previous);
+                }
+                if let Some(direction) = direction {
+                    self.held.press(direction, now);
+                }
+                self.stick = direction;
+            }
+        }
+    }
+    /** Directions to move in this simulation step, see `HeldKeys::movement`. */
+    pub fn movement(&mut self, now: time::Instant) -> Vec<Action> {
+        return self.held.movement(now);
+    }
+}
+
+/** Connected gamepads, read through gilrs. */
+#[cfg(feature = "gamepad")]
+pub struct Pads {
+    gilrs: gilrs::Gilrs,
+}
+
+#[cfg(feature = "gamepad")]
+impl Pads {
+    pub fn new() -> Result<Self, String> {
+        return match gilrs::Gilrs::new() {
+            Ok(gilrs) => Ok(Pads { gilrs }),
+            Err(e) => Err(format!("Couldn't initialize gamepad support, {e}")),
+        };
+    }
+    /** Drain every pending gamepad event, without blocking. */
+    pub fn poll(&mut self) -> Vec<PadEvent> {
+        use gilrs::{Axis, Button, EventType};
+
+        let button = |button: Button| {
+            return match button {
+                Button::DPadUp => Some(PadButton::Up),
+                Button::DPadDown => Some(PadButton::Down),
+                Button::DPadLeft => Some(PadButton::Left),
+                Button::DPadRight => Some(PadButton::Right),
+                Button::South => Some(PadButton::South),
+                Button::Start => Some(PadButton::Start),
+                _ => None,
+            };
+        };
+
+        let mut events: Vec<PadEvent> = vec![];
+        while let Some(gilrs::Event { event, .. }) = self.gilrs.next_event() {
+            match event {
+                EventType::ButtonPressed(pressed, _) => events.extend(button(pressed).map(PadEvent::Pressed)),
+                EventType::ButtonReleased(released, _) => events.extend(button(released).map(PadEvent::Released)),
+                EventType::AxisChanged(Axis::LeftStickX, x, _) => events.push(PadEvent::StickX(x)),
+                _ => {}
+            }
+        }
+
+        return events;
+    }
+}
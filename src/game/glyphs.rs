@@ -0,0 +1,199 @@
+/**
+ * Command-line Space Invaders, personal introduction to systems-level programming with Rust.
+ * @author Maxylan (https://github.com/Maxylan)
+ * @license MIT
+ */
+use std::env;
+use unicode_width::UnicodeWidthStr;
+
+/** Widest sprite (in terminal cells) the alien, player and UFO glyphs may be themed with. */
+pub const MAX_SPRITE_WIDTH: usize = 5;
+
+/**
+ * Every character drawn to the screen, so limited terminals can be given a pure-ASCII set,
+ * and users can theme the art by overriding individual entries (`--glyph <name>=<glyph>`).
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlyphMap {
+    /** Player sprite, one char per cell. */
+    pub player: String,
+    /** Cell of the player sprite shots are fired from, counting from its left-most (the anchor), always one of its cells (see `set`). */
+    pub player_origin: u16,
+    /** Alien sprite, one char per cell (aliens are as wide as their sprite). */
+    pub alien: String,
+    /** Sprite of an intact front-row alien, one char per cell. Once cracked, it's drawn as a regular `alien`. */
+    pub shielded: String,
+    /** UFO sprite, one char per cell. */
+    pub ufo: String,
+    /** Fragment of a split alien (see `Arguments.split_aliens`), a single cell. */
+    pub fragment: char,
+    pub projectile: char,
+    pub enemy_shot: char,
+    pub star: char,
+    pub barrier: char,
+    /** The single line separating the HUD from the play area, play rows being filled with `background_char` instead. */
+    pub divider: char,
+    /** Shading framing the screen during a "bullet time" slowdown. */
+    pub vignette: char,
+}
+
+impl GlyphMap {
+    /** Glyph set for terminals capable of rendering unicode (box-drawing and the like). */
+    pub fn unicode() -> Self {
+        return GlyphMap {
+            player: String::from("◢▲◣"),
+            player_origin: 1,
+            alien: String::from("<Ѫ>"),
+            shielded: String::from("[Ѫ]"),
+            ufo: String::from("<◉>"),
+            fragment: 'ѫ',
+            projectile: '│',
+            enemy_shot: '¦',
+            star: '·',
+            barrier: '█',
+            divider: '═',
+            vignette: '░',
+        };
+    }
+    /** Glyph set using only printable ASCII (`0x20..0x7E`), safe on any terminal. */
+    pub fn ascii() -> Self {
+        return GlyphMap {
+            player: String::from("/^\\"),
+            player_origin: 1,
+            alien: String::from("/W\\"),
+            shielded: String::from("[W]"),
+            ufo: String::from("<O>"),
+            fragment: 'w',
+            projectile: '|',
+            enemy_shot: '!',
+            star: '.',
+            barrier: '#',
+            divider: '=',
+            vignette: ':',
+        };
+    }
+    /**
+     * Pick a glyph set at startup, `ascii` forces the ASCII set (`--ascii`).
+     * Otherwise falls back to ASCII when the terminal/locale doesn't look unicode-capable.
+     */
+    pub fn detect(ascii: bool) -> Self {
+        if ascii || !supports_unicode() {
+            return GlyphMap::ascii();
+        }
+
+        return GlyphMap::unicode();
+    }
+    /**
+     * Override a single entry by name, validating the glyph first.
+     * Sprites (`player`, `alien`, `shielded`, `ufo`) may be up to `MAX_SPRITE_WIDTH` cells, everything else is exactly one.
+     * Bar `player_origin`, a cell of the `player` sprite, which is re-checked whenever either of the two changes
+     * (a narrower ship needing its origin set first). Left unchanged should the override be rejected.
+     */
+    pub fn set(&mut self, name: &str, glyph: &str) -> Result<(), String> {
+        let mut updated = self.clone();
+        updated.assign(name, glyph)?;
+        updated.check_origin()?;
+        *self = updated;
+        return Ok(());
+    }
+    /**
+     * Apply a list of `(name, glyph)` overrides, in order. The `player_origin` is only checked against the `player`
+     * sprite once all of them were, so the two may be given in either order.
+     */
+    pub fn with_overrides(mut self, overrides: &[(String, String)]) -> Result<Self, String> {
+        for (name, glyph) in overrides {
+            self.assign(name, glyph)?;
+        }
+
+        self.check_origin()?;
+        return Ok(self);
+    }
+    /** Override a single entry by name, validating the glyph on its own. */
+    fn assign(&mut self, name: &str, glyph: &str) -> Result<(), String> {
+        match name {
+            "player" => self.player = sprite(name, glyph)?,
+            "player_origin" => self.player_origin = origin(name, glyph)?,
+            "alien" => self.alien = sprite(name, glyph)?,
+            "shielded" => self.shielded = sprite(name, glyph)?,
+            "ufo" => self.ufo = sprite(name, glyph)?,
+            "fragment" => self.fragment = single(name, glyph)?,
+            "projectile" => self.projectile = single(name, glyph)?,
+            "enemy_shot" => self.enemy_shot = single(name, glyph)?,
+            "star" => self.star = single(name, glyph)?,
+            "barrier" => self.barrier = single(name, glyph)?,
+            "divider" => self.divider = single(name, glyph)?,
+            "vignette" => self.vignette = single(name, glyph)?,
+            _ => return Err(format!("Unknown glyph '{name}'")),
+        }
+
+        return Ok(());
+    }
+    /** Whether `player_origin` is a cell of the `player` sprite. */
+    fn check_origin(&self) -> Result<(), String> {
+        let width = self.player.width();
+        if (self.player_origin as usize) < width {
+            return Ok(());
+        }
+
+        return Err(format!("Glyph 'player_origin' must be a cell of the 'player' sprite, 0 to {}, got '{}'", width - 1, self.player_origin));
+    }
+}
+
+/** Whether the glyph can be drawn in a single cell, i.e not a control character. */
+fn printable(glyph: char) -> bool {
+    return !glyph.is_control();
+}
+
+/** Validate a single-cell glyph. */
+fn single(name: &str, glyph: &str) -> Result<char, String> {
+    let mut chars = glyph.chars();
+    return match (chars.next(), chars.next()) {
+        (Some(character), None) if printable(character) => Ok(character),
+        _ => Err(format!("Glyph '{name}' must be a single printable character, got '{glyph}'")),
+    };
+}
+
+/** Validate a (fixed-width) sprite. */
+fn sprite(name: &str, glyph: &str) -> Result<String, String> {
+    let width = glyph.width();
+    if width == 0 || width > MAX_SPRITE_WIDTH || !glyph.chars().all(printable) {
+        return Err(format!(
+            "Glyph '{name}' must be 1 to {MAX_SPRITE_WIDTH} printable characters, got '{glyph}'"
+        ));
+    }
+
+    return Ok(String::from(glyph));
+}
+
+/** Validate a cell of a sprite, whichever sprite it's a cell of being checked by the caller (see `check_origin`). */
+fn origin(name: &str, cell: &str) -> Result<u16, String> {
+    return match cell.parse::<u16>() {
+        Ok(cell) if (cell as usize) < MAX_SPRITE_WIDTH => Ok(cell),
+        _ => Err(format!("Glyph '{name}' must be a cell of the sprite, 0 to {}, got '{cell}'", MAX_SPRITE_WIDTH - 1)),
+    };
+}
+
+/**
+ * Best-effort guess at whether the terminal can render unicode, based on `TERM` and the locale.
+ * The first locale variable set (`LC_ALL`, `LC_CTYPE`, `LANG`) decides, like it would for libc.
+ */
+pub fn supports_unicode() -> bool {
+    if let Ok(term) = env::var("TERM") {
+        if term == "dumb" || term == "linux" || term == "vt100" {
+            return false;
+        }
+    }
+
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|key| env::var(key).ok())
+        .find(|value| !value.is_empty());
+
+    return match locale {
+        Some(locale) => {
+            let locale = locale.to_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        }
+        None => false,
+    };
+}
@@ -0,0 +1,433 @@
+/**
+ * Command-line Space Invaders, personal introduction to systems-level programming with Rust.
+ * @author Maxylan (https://github.com/Maxylan)
+ * @license MIT
+ */
+use std::{collections::VecDeque, io, io::Write, time};
+
+use crossterm::{
+    cursor,
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers,
+        MouseButton, MouseEvent, MouseEventKind, PopKeyboardEnhancementFlags,
+    },
+    execute, terminal,
+};
+
+use super::Action;
+
+/**
+ * How movement buffered up between two simulation steps is resolved.
+ * At low framerates several key presses may arrive within a single step.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputMode {
+    /** Apply every buffered action, in order. */
+    Queue,
+    /** Only the most recent direction is applied. */
+    LastWins,
+    /** Opposite directions cancel each other out, the net movement is applied. */
+    Cancel,
+}
+
+/** Which actions keys are mapped to, depending on what's on screen. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Keymap {
+    /** Playing the game. */
+    Game,
+    /** Navigating the pause menu, where `Esc` resumes rather than quits. */
+    Menu,
+    /** Typing in initials for the leaderboard, where letters are just letters. */
+    Text,
+}
+
+/**
+ * How long a movement key counts as held after its latest press (or OS key-repeat), on terminals that don't
+ * report key releases, unless told otherwise (`hold_timeout`). Longer than the usual delay before the OS starts
+ * repeating a key (250 to 500ms), so a held key doesn't stall between its first press and first repeat.
+ */
+pub const HOLD_TIMEOUT: time::Duration = time::Duration::from_millis(500);
+
+/**
+ * Puts the terminal into raw mode (and hides the cursor) for as long as it lives,
+ * restoring it when dropped, even when unwinding from a panic.
+ * Key releases are reported where possible (see `HeldKeys`), and mouse capture is enabled when asked for.
+ */
+pub struct TerminalGuard {
+    /** Whether keyboard enhancement flags were pushed, and so have to be popped again. */
+    enhanced: bool,
+    mouse: bool,
+    /** Whether the window title was saved, to be restored once the game sets its own (`set_title`). */
+    title: bool,
+}
+
+/** Saves the window title (and icon name) on xterm-compatible terminals, see `RESTORE_TITLE`. */
+const SAVE_TITLE: &str = "\x1b[22;0t";
+/** Restores the window title saved by `SAVE_TITLE`. */
+const RESTORE_TITLE: &str = "\x1b[23;0t";
+
+impl TerminalGuard {
+    pub fn new(mouse: bool, title: bool) -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), cursor::Hide)?;
+        if mouse {
+            execute!(io::stdout(), EnableMouseCapture)?;
+        }
+        if title {
+            // Push the current title onto the terminal's (xterm) title stack.
+            write!(io::stdout(), "{SAVE_TITLE}")?;
+        }
+
+        return Ok(TerminalGuard { enhanced: enable_releases()?, mouse, title });
+    }
+    /** Whether the terminal reports key releases. */
+    pub fn reports_releases(&self) -> bool {
+        return cfg!(windows) || self.enhanced;
+    }
+}
+
+/**
+ * Windows' console always reports key releases (and doesn't implement the enhancement protocol),
+ * so there's nothing to enable. Returns whether enhancement flags were pushed.
+ */
+#[cfg(windows)]
+fn enable_releases() -> io::Result<bool> {
+    return Ok(false);
+}
+
+/**
+ * Unix terminals only report key releases when asked to, through keyboard enhancement flags,
+ * which not every terminal supports. Returns whether enhancement flags were pushed.
+ */
+#[cfg(not(windows))]
+fn enable_releases() -> io::Result<bool> {
+    use crossterm::event::{KeyboardEnhancementFlags, PushKeyboardEnhancementFlags};
+
+    let supported = terminal::supports_keyboard_enhancement().unwrap_or(false);
+    if supported {
+        execute!(io::stdout(), PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES))?;
+    }
+
+    return Ok(supported);
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        if self.mouse {
+            let _ = execute!(io::stdout(), DisableMouseCapture);
+        }
+        if self.enhanced {
+            let _ = execute!(io::stdout(), PopKeyboardEnhancementFlags);
+        }
+        if self.title {
+            let _ = write!(io::stdout(), "{RESTORE_TITLE}");
+        }
+        let _ = execute!(io::stdout(), cursor::Show);
+        let _ = terminal::disable_raw_mode();
+        let _ = io::stdout().flush();
+    }
+}
+
+/**
+ * Map a key press to the action it's bound to, if any. Presses and OS key-repeats (reported as repeats
+ * on some platforms, as further presses on others) are treated alike, releases are ignored.
+ * Which action depends on the `keymap`, see `Keymap`.
+ */
+pub fn map_key(key: KeyEvent, keymap: Keymap) -> Option<Action> {
+    if key.kind == KeyEventKind::Release {
+        return None;
+    }
+
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        return Some(Action::Quit);
+    }
+    if key.code == KeyCode::F(1) {
+        return Some(Action::ToggleInspector);
+    }
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        match key.code {
+            KeyCode::Char('+') | KeyCode::Char('=') => return Some(Action::ScaleUp),
+            // Without keyboard enhancements `Ctrl -` arrives as the same control byte (0x1f) as `Ctrl 7`.
+            KeyCode::Char('-') | KeyCode::Char('7') => return Some(Action::ScaleDown),
+            _ => {}
+        }
+    }
+
+    if keymap == Keymap::Text {
+        return match key.code {
+            KeyCode::Char(letter) => Some(Action::Type(letter)),
+            KeyCode::Backspace => Some(Action::Erase),
+            KeyCode::Enter => Some(Action::Confirm),
+            KeyCode::Esc => Some(Action::Quit),
+            _ => None,
+        };
+    }
+    if keymap == Keymap::Menu {
+        return match key.code {
+            KeyCode::Up | KeyCode::Char('w') => Some(Action::MenuUp),
+            KeyCode::Down | KeyCode::Char('s') => Some(Action::MenuDown),
+            KeyCode::Enter | KeyCode::Char(' ') => Some(Action::Confirm),
+            KeyCode::Esc | KeyCode::Char('p') => Some(Action::Pause),
+            KeyCode::Char('q') => Some(Action::Quit),
+            _ => None,
+        };
+    }
+
+    if let Some(direction) = direction(key.code) {
+        return Some(direction);
+    }
+
+    return match key.code {
+        KeyCode::Char(' ') | KeyCode::Up | KeyCode::Char('w') => Some(Action::Fire),
+        KeyCode::Char('p') => Some(Action::Pause),
+        KeyCode::Char('t') => Some(Action::CycleTheme),
+        KeyCode::Char('c') => Some(Action::Continue),
+        KeyCode::Char('i') => Some(Action::CursorUp),
+        KeyCode::Char('k') => Some(Action::CursorDown),
+        KeyCode::Char('j') => Some(Action::CursorLeft),
+        KeyCode::Char('l') => Some(Action::CursorRight),
+        KeyCode::Esc | KeyCode::Char('q') => Some(Action::Quit),
+        _ => None,
+    };
+}
+
+/**
+ * Map a mouse event to an action (mouse input, see `Arguments.mouse`). Moving the mouse puts the ship
+ * under the pointer, a left click fires.
+ */
+pub fn map_mouse(mouse: MouseEvent) -> Option<Action> {
+    return match mouse.kind {
+        MouseEventKind::Moved | MouseEventKind::Drag(_) => Some(Action::MoveTo(mouse.column)),
+        MouseEventKind::Down(MouseButton::Left) => Some(Action::Fire),
+        _ => None,
+    };
+}
+
+/** Movement direction a key is bound to, if any. */
+fn direction(code: KeyCode) -> Option<Action> {
+    return match code {
+        KeyCode::Left | KeyCode::Char('a') => Some(Action::MoveLeft),
+        KeyCode::Right | KeyCode::Char('d') => Some(Action::MoveRight),
+        _ => None,
+    };
+}
+
+/** Tracking of a single movement key, see `HeldKeys`. */
+#[derive(Debug, Default, Clone, Copy)]
+struct KeyState {
+    /** Latest press (or OS key-repeat), `None` once released. */
+    last_seen: Option<time::Instant>,
+    /** Pressed since the latest simulation step, so even a tap shorter than a step moves the ship. */
+    tapped: bool,
+}
+
+/**
+ * Movement keys currently held down, so the ship moves every simulation step a direction is held,
+ * independent of the OS key-repeat rate. Keys are held until released, or on terminals that don't report
+ * releases, until `timeout` after the latest press / repeat.
+ */
+#[derive(Debug)]
+pub struct HeldKeys {
+    releases: bool,
+    /** How long a key counts as held after its latest press without releases, `HOLD_TIMEOUT` by default. */
+    timeout: time::Duration,
+    left: KeyState,
+    right: KeyState,
+}
+
+impl HeldKeys {
+    pub fn new(releases: bool) -> Self {
+        return HeldKeys { releases, timeout: HOLD_TIMEOUT, left: KeyState::default(), right: KeyState::default() };
+    }
+    /** Count keys as held for `timeout` after their latest press, on terminals that don't report releases. */
+    pub fn with_timeout(mut self, timeout: time::Duration) -> Self {
+        self.timeout = timeout;
+        return self;
+    }
+    fn key(&mut self, direction: Action) -> Option<&mut KeyState> {
+        return match direction {
+            Action::MoveLeft => Some(&mut self.left),
+            Action::MoveRight => Some(&mut self.right),
+            _ => None,
+        };
+    }
+    /** A movement key was pressed (or repeated by the OS) at `now`. */
+    pub fn press(&mut self, direction: Action, now: time::Instant) {
+        if let Some(key) = self.key(direction) {
+            key.last_seen = Some(now);
+            key.tapped = true;
+        }
+    }
+    /** A movement key was released. */
+    pub fn release(&mut self, direction: Action) {
+        if let Some(key) = self.key(direction) {
+            key.last_seen = None;
+        }
+    }
+    /**
+     * Directions to move in this simulation step, the most recently pressed last (for `InputMode::LastWins`).
+     * Forgets taps, so should be called exactly once per step.
+     */
+    pub fn movement(&mut self, now: time::Instant) -> Vec<Action> {
+        let (releases, timeout) = (self.releases, self.timeout);
+        let is_held = |key: &KeyState| {
+            return key.last_seen.is_some_and(|seen| releases || now.duration_since(seen) < timeout);
+        };
+
+        let mut held: Vec<(time::Instant, Action)> = vec![];
+        for (key, direction) in [(&self.left, Action::MoveLeft), (&self.right, Action::MoveRight)] {
+            if key.tapped || is_held(key) {
+                held.push((key.last_seen.unwrap_or(now), direction));
+            }
+        }
+
+        self.left.tapped = false;
+        self.right.tapped = false;
+        held.sort_by_key(|(seen, _)| *seen);
+        return held.into_iter().map(|(_, direction)| direction).collect();
+    }
+}
+
+/** A single input buffered on an `InputQueue`. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Input {
+    Action(Action),
+    /** A movement key pressed (or repeated by the OS) at the given time, see `HeldKeys::press`. */
+    Press(Action, time::Instant),
+    /** A movement key released, see `HeldKeys::release`. */
+    Release(Action),
+}
+
+/**
+ * Input buffered until the next simulation step. Bounded, the oldest actions and movement key presses being dropped
+ * past its capacity, so events piling up while the game stalls (a terminal hiccup, say) don't all play out in a burst
+ * afterwards. Releases are never dropped (nor counted), lest a key be left held that was let go of.
+ */
+#[derive(Debug, Clone)]
+pub struct InputQueue {
+    inputs: VecDeque<Input>,
+    capacity: usize,
+}
+
+impl InputQueue {
+    /** An empty queue holding up to `capacity` actions and presses (at least one). */
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        return InputQueue { inputs: VecDeque::with_capacity(capacity), capacity };
+    }
+    /** Queue up an input, dropping the oldest action or press should the queue be full. */
+    fn queue(&mut self, input: Input) {
+        let is_release = |input: &Input| matches!(input, Input::Release(_));
+        if !is_release(&input) && self.inputs.iter().filter(|queued| !is_release(queued)).count() == self.capacity {
+            if let Some(oldest) = self.inputs.iter().position(|queued| !is_release(queued)) {
+                self.inputs.remove(oldest);
+            }
+        }
+
+        self.inputs.push_back(input);
+    }
+    /** Queue up an action, dropping the oldest one should the queue be full. */
+    pub fn push(&mut self, action: Action) {
+        self.queue(Input::Action(action));
+    }
+    /** Queue up a movement key's press (or OS key-repeat) at `now`, to be tracked on `HeldKeys` come the next step. */
+    pub fn press(&mut self, direction: Action, now: time::Instant) {
+        self.queue(Input::Press(direction, now));
+    }
+    /** Queue up a movement key's release. */
+    pub fn release(&mut self, direction: Action) {
+        self.queue(Input::Release(direction));
+    }
+    pub fn contains(&self, action: &Action) -> bool {
+        return self.inputs.contains(&Input::Action(*action));
+    }
+    /**
+     * Take every queued input, oldest first, leaving the queue empty. Movement keys are tracked on `held`
+     * (in the order they were pressed and released), every other action is returned.
+     */
+    pub fn drain(&mut self, held: &mut HeldKeys) -> Vec<Action> {
+        let mut actions: Vec<Action> = vec![];
+        for input in self.inputs.drain(..) {
+            match input {
+                Input::Action(action) => actions.push(action),
+                Input::Press(direction, seen) => held.press(direction, seen),
+                Input::Release(direction) => held.release(direction),
+            }
+        }
+
+        return actions;
+    }
+}
+
+/**
+ * Queue up a single terminal event read at `now` on `buffer`, movement keys' presses and releases included
+ * (tracked on `HeldKeys` once drained). Mouse input is only taken while playing.
+ */
+pub fn handle(event: Event, buffer: &mut InputQueue, keymap: Keymap, now: time::Instant) {
+    let playing = keymap == Keymap::Game;
+    if let Event::Mouse(mouse) = event {
+        if let Some(action) = map_mouse(mouse).filter(|_| playing) {
+            buffer.push(action);
+        }
+        return;
+    }
+
+    if let Event::Key(key) = event {
+        if let Some(direction) = direction(key.code).filter(|_| playing) {
+            match key.kind {
+                KeyEventKind::Release => buffer.release(direction),
+                _ => buffer.press(direction, now),
+            }
+            return;
+        }
+
+        if let Some(action) = map_key(key, keymap) {
+            buffer.push(action);
+        }
+    }
+}
+
+/** Drain every pending terminal event without blocking, queueing them up on `buffer` (see `handle`). */
+pub fn poll(buffer: &mut InputQueue, keymap: Keymap) -> io::Result<()> {
+    while event::poll(time::Duration::ZERO)? {
+        handle(event::read()?, buffer, keymap, time::Instant::now());
+    }
+
+    return Ok(());
+}
+
+/** Resolve the actions buffered since the last simulation step, according to `mode`. */
+pub fn resolve(buffer: &[Action], mode: InputMode) -> Vec<Action> {
+    if mode == InputMode::Queue {
+        return buffer.to_vec();
+    }
+
+    let is_movement = |action: &Action| matches!(action, Action::MoveLeft | Action::MoveRight);
+    let mut actions: Vec<Action> = buffer.iter().copied().filter(|action| !is_movement(action)).collect();
+
+    let movement = match mode {
+        InputMode::LastWins => buffer.iter().copied().rfind(is_movement),
+        _ => {
+            let net: i32 = buffer
+                .iter()
+                .map(|action| match action {
+                    Action::MoveLeft => -1,
+                    Action::MoveRight => 1,
+                    _ => 0,
+                })
+                .sum();
+
+            match net {
+                0 => None,
+                net if net < 0 => Some(Action::MoveLeft),
+                _ => Some(Action::MoveRight),
+            }
+        }
+    };
+
+    if let Some(movement) = movement {
+        actions.insert(0, movement);
+    }
+
+    return actions;
+}
@@ -0,0 +1,66 @@
+/**
+ * Command-line Space Invaders, personal introduction to systems-level programming with Rust.
+ * @author Maxylan (https://github.com/Maxylan)
+ * @license MIT
+ */
+use termsize::Size;
+
+use super::{
+    entities::{Owner, Pos},
+    Action, EntityRef, GameState,
+};
+
+/** Columns reserved for the inspector's side panel, its leading gap included. */
+pub const PANEL_WIDTH: u16 = 30;
+
+/**
+ * Debug overlay (toggled with `F1`), a side panel of live state along with a cursor
+ * that can be moved around the arena (`i`, `j`, `k`, `l`) to see what occupies a cell.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Inspector {
+    pub cursor: Pos,
+}
+
+impl Inspector {
+    /** Move the cursor, keeping it within an arena of the given size. */
+    pub fn handle(&mut self, action: Action, size: &Size) {
+        match action {
+            Action::CursorUp => self.cursor.row = self.cursor.row.saturating_sub(1),
+            Action::CursorDown => self.cursor.row += 1,
+            Action::CursorLeft => self.cursor.col = self.cursor.col.saturating_sub(1),
+            Action::CursorRight => self.cursor.col += 1,
+            _ => return,
+        }
+
+        self.cursor = self.cursor.clamped(size);
+    }
+    /** Contents of the side panel, one line each. */
+    pub fn lines(&self, state: &GameState) -> Vec<String> {
+        let shots = |owner: Owner| state.projectiles.iter().filter(|projectile| projectile.owner == owner).count();
+        let under = match state.entity_at(self.cursor) {
+            Some(EntityRef::Player) => String::from("player"),
+            Some(EntityRef::Alien(index)) => format!("alien #{} ({} hp)", state.enemies[index].id, state.enemies[index].hp),
+            Some(EntityRef::Ufo) => String::from("ufo"),
+            Some(EntityRef::Projectile(index)) => match state.projectiles[index].owner {
+                Owner::Player => format!("shot #{index}"),
+                Owner::Enemy => format!("enemy shot #{index}"),
+            },
+            Some(EntityRef::Star(index)) => format!("star #{index}"),
+            None => String::from("nothing"),
+        };
+
+        return vec![
+            String::from("INSPECTOR (F1)"),
+            format!("Frame:     {}", state.frame),
+            format!("Aliens:    {}", state.enemies.len()),
+            format!("Shots:     {} up, {} down", shots(Owner::Player), shots(Owner::Enemy)),
+            format!("Stars:     {}", state.falling_stars.len()),
+            format!("UFO:       {}", if state.ufo.is_some() { "yes" } else { "no" }),
+            format!("Player:    {},{}", state.player.pos.row, state.player.pos.col),
+            format!("Direction: {}", if state.enemy_direction < 0 { "left" } else { "right" }),
+            format!("Cursor:    {},{}", self.cursor.row, self.cursor.col),
+            format!("Under:     {under}"),
+        ];
+    }
+}
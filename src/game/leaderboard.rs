@@ -0,0 +1,142 @@
+/**
+ * Command-line Space Invaders, personal introduction to systems-level programming with Rust.
+ * @author Maxylan (https://github.com/Maxylan)
+ * @license MIT
+ */
+use std::{fs, io, io::Write, path::Path, time};
+
+use super::Action;
+
+/** Entries kept on the leaderboard, lower scores fall off the bottom. */
+pub const MAX_ENTRIES: usize = 10;
+/** Letters of a player's initials. */
+pub const INITIALS: usize = 3;
+
+/** A single score on the leaderboard. */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub score: u32,
+    pub initials: String,
+    /** Day the score was set, as `YYYY-MM-DD` (UTC). */
+    pub date: String,
+}
+
+/**
+ * The top `MAX_ENTRIES` scores, best first, persisted as one `score,initials,date` line per entry.
+ * Ties keep their order of arrival, the older score placing higher.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Leaderboard {
+    entries: Vec<Entry>,
+}
+
+impl Leaderboard {
+    /** Load the leaderboard at `path`, a missing file being an empty leaderboard. Malformed lines are skipped. */
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Leaderboard::default()),
+            Err(e) => return Err(e),
+        };
+
+        let mut leaderboard = Leaderboard::default();
+        for line in contents.lines() {
+            let mut fields = line.split(',');
+            let (Some(score), Some(initials), Some(date)) = (fields.next(), fields.next(), fields.next()) else {
+                continue;
+            };
+            if let Ok(score) = score.trim().parse::<u32>() {
+                leaderboard.insert(Entry { score, initials: initials.trim().to_string(), date: date.trim().to_string() });
+            }
+        }
+
+        return Ok(leaderboard);
+    }
+    /** Write the leaderboard to `path`, replacing whatever was there. */
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut file = fs::File::create(path)?;
+        for entry in self.entries.iter() {
+            writeln!(file, "{},{},{}", entry.score, entry.initials, entry.date)?;
+        }
+
+        return Ok(());
+    }
+    /** Whether `score` would make it onto the leaderboard. */
+    pub fn qualifies(&self, score: u32) -> bool {
+        return score > 0 && (self.entries.len() < MAX_ENTRIES || self.entries.iter().any(|entry| score > entry.score));
+    }
+    /** Place `entry` by its score, dropping whatever falls off the bottom. Returns its place (`0` being best), if it made it. */
+    pub fn insert(&mut self, entry: Entry) -> Option<usize> {
+        let place = self.entries.iter().position(|other| entry.score > other.score).unwrap_or(self.entries.len());
+        if place >= MAX_ENTRIES {
+            return None;
+        }
+
+        self.entries.insert(place, entry);
+        self.entries.truncate(MAX_ENTRIES);
+        return Some(place);
+    }
+    /** The leaderboard as it's drawn on the title and game over screens, one line each. */
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![String::from("HIGH SCORES")];
+        if self.entries.is_empty() {
+            lines.push(String::from("No scores yet"));
+        }
+        for (place, entry) in self.entries.iter().enumerate() {
+            lines.push(format!("{:>2}. {:<3} {:>6}  {}", place + 1, entry.initials, entry.score, entry.date));
+        }
+
+        return lines;
+    }
+}
+
+/** Initials being typed in after a qualifying score, shown on the game over screen until confirmed. */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InitialsEntry {
+    pub initials: String,
+}
+
+impl InitialsEntry {
+    /** Type a letter or take one back. Returns the initials once confirmed, with all of them typed in. */
+    pub fn handle(&mut self, action: Action) -> Option<String> {
+        match action {
+            Action::Type(letter) if letter.is_ascii_alphanumeric() && self.initials.len() < INITIALS => {
+                self.initials.push(letter.to_ascii_uppercase());
+            }
+            Action::Erase => {
+                self.initials.pop();
+            }
+            Action::Confirm if self.initials.len() == INITIALS => return Some(self.initials.clone()),
+            _ => {}
+        }
+
+        return None;
+    }
+    /** Prompt drawn on the game over screen, blanks marking the letters left to type. */
+    pub fn lines(&self) -> Vec<String> {
+        let blanks = "_".repeat(INITIALS - self.initials.len());
+        return vec![String::from("NEW HIGH SCORE!"), format!("Enter your initials: {}{blanks}", self.initials)];
+    }
+}
+
+/** Today's date (UTC) as `YYYY-MM-DD`. */
+pub fn today() -> String {
+    let seconds = time::SystemTime::now().duration_since(time::UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    return format!("{year:04}-{month:02}-{day:02}");
+}
+
+/** Calendar date of the given day since the Unix epoch (Howard Hinnant's `civil_from_days`). */
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    return (year, month, day);
+}
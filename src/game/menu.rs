@@ -0,0 +1,71 @@
+/**
+ * Command-line Space Invaders, personal introduction to systems-level programming with Rust.
+ * @author Maxylan (https://github.com/Maxylan)
+ * @license MIT
+ */
+use serde::{Deserialize, Serialize};
+
+use super::Action;
+
+/** Entries of the pause menu, top to bottom. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PauseOption {
+    Resume,
+    Restart,
+    Quit,
+}
+
+impl PauseOption {
+    pub const ALL: [PauseOption; 3] = [PauseOption::Resume, PauseOption::Restart, PauseOption::Quit];
+
+    pub fn label(&self) -> &'static str {
+        return match self {
+            PauseOption::Resume => "Resume",
+            PauseOption::Restart => "Restart",
+            PauseOption::Quit => "Quit",
+        };
+    }
+}
+
+/** Overlay shown while the game is paused, `selected` being the highlighted entry. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PauseMenu {
+    pub selected: PauseOption,
+}
+
+impl Default for PauseMenu {
+    fn default() -> Self {
+        return PauseMenu { selected: PauseOption::Resume };
+    }
+}
+
+impl PauseMenu {
+    /**
+     * Apply a single action to the menu, moving the highlight (wrapping around at either end).
+     * Returns the option picked, should the action confirm a selection. Pausing again is the same as picking `Resume`.
+     */
+    pub fn handle(&mut self, action: Action) -> Option<PauseOption> {
+        let index = PauseOption::ALL.iter().position(|option| *option == self.selected).unwrap_or(0);
+        let count = PauseOption::ALL.len();
+
+        match action {
+            Action::MenuUp => self.selected = PauseOption::ALL[(index + count - 1) % count],
+            Action::MenuDown => self.selected = PauseOption::ALL[(index + 1) % count],
+            Action::Confirm => return Some(self.selected),
+            Action::Pause => return Some(PauseOption::Resume),
+            _ => {}
+        }
+
+        return None;
+    }
+    /** One line per entry, the highlighted one marked with arrows. */
+    pub fn lines(&self) -> Vec<String> {
+        return PauseOption::ALL
+            .iter()
+            .map(|option| match *option == self.selected {
+                true => format!("> {} <", option.label()),
+                false => format!("  {}  ", option.label()),
+            })
+            .collect();
+    }
+}
@@ -0,0 +1,118 @@
+/**
+ * Command-line Space Invaders, personal introduction to systems-level programming with Rust.
+ * @author Maxylan (https://github.com/Maxylan)
+ * @license MIT
+ */
+use std::{collections::VecDeque, time::Duration};
+
+/** Upper bounds (exclusive, in milliseconds) of the histogram's buckets, the last bucket catching everything slower. */
+const BUCKETS_MS: [u64; 5] = [10, 20, 50, 100, 250];
+/** Width, in cells, of the histogram's longest bar. */
+const BAR_WIDTH: usize = 40;
+
+/**
+ * The most recent frame times (time between two presented frames), kept in a bounded ring buffer
+ * so long sessions don't grow without limit. Reported on exit with `--profile`.
+ */
+#[derive(Debug)]
+pub struct FrameTimes {
+    samples: VecDeque<Duration>,
+    capacity: usize,
+}
+
+impl FrameTimes {
+    pub fn new(capacity: usize) -> Self {
+        return FrameTimes { samples: VecDeque::with_capacity(capacity), capacity };
+    }
+    /** Add a sample, dropping the oldest once full. */
+    pub fn record(&mut self, frame_time: Duration) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+
+        self.samples.push_back(frame_time);
+    }
+    pub fn len(&self) -> usize {
+        return self.samples.len();
+    }
+    /** The `percentile`th (`0` to `100`) frame time, by nearest rank. `None` without any samples. */
+    pub fn percentile(&self, percentile: f32) -> Option<Duration> {
+        if self.samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted: Vec<Duration> = self.samples.iter().copied().collect();
+        sorted.sort();
+
+        let rank = (percentile.clamp(0_f32, 100_f32) / 100_f32 * sorted.len() as f32).ceil() as usize;
+        return Some(sorted[rank.saturating_sub(1).min(sorted.len() - 1)]);
+    }
+    /** Number of samples falling in each bucket of `BUCKETS_MS`, plus one for everything slower. */
+    pub fn histogram(&self) -> Vec<usize> {
+        let mut counts = vec![0_usize; BUCKETS_MS.len() + 1];
+        for sample in self.samples.iter() {
+            let millis = sample.as_millis() as u64;
+            let bucket = BUCKETS_MS.iter().position(|bound| millis < *bound).unwrap_or(BUCKETS_MS.len());
+            counts[bucket] += 1;
+        }
+
+        return counts;
+    }
+    /** Percentiles and a histogram of the recorded samples, one line each. */
+    pub fn report(&self) -> Vec<String> {
+        let (Some(p50), Some(p95), Some(p99)) = (self.percentile(50_f32), self.percentile(95_f32), self.percentile(99_f32)) else {
+            return vec![String::from("Frame times: no frames were rendered")];
+        };
+
+        let ms = |duration: Duration| duration.as_secs_f64() * 1000_f64;
+        let mut lines = vec![
+            format!("Frame times ({} samples):", self.len()),
+            format!("  p50: {:.1}ms | p95: {:.1}ms | p99: {:.1}ms", ms(p50), ms(p95), ms(p99)),
+        ];
+
+        let counts = self.histogram();
+        let most = counts.iter().copied().max().unwrap_or(0).max(1);
+        for (index, count) in counts.iter().enumerate() {
+            let label = match BUCKETS_MS.get(index) {
+                Some(bound) => format!("< {bound:>3}ms"),
+                None => format!(">= {:>3}ms", BUCKETS_MS[BUCKETS_MS.len() - 1]),
+            };
+            let bar = "#".repeat((count * BAR_WIDTH).div_ceil(most));
+            lines.push(format!("  {label:>8} | {bar} {count}"));
+        }
+
+        return lines;
+    }
+}
+
+/** Consecutive slow frames it takes to conclude the terminal can't keep up, see `SlowFrames`. */
+pub const SLOW_FRAME_STREAK: u32 = 24;
+
+/**
+ * Watches for a terminal that can't keep up with the frame rate: frames taking half again their budget
+ * (the frame rate's target) or longer, `SLOW_FRAME_STREAK` times in a row. Never trips without a budget (uncapped).
+ */
+#[derive(Debug)]
+pub struct SlowFrames {
+    budget: Duration,
+    streak: u32,
+}
+
+impl SlowFrames {
+    pub fn new(budget: Duration) -> Self {
+        return SlowFrames { budget, streak: 0 };
+    }
+    /** Add a frame time, returns `true` (once) as the streak of slow frames reaches `SLOW_FRAME_STREAK`. */
+    pub fn record(&mut self, frame_time: Duration) -> bool {
+        if self.budget.is_zero() || frame_time < self.budget * 3 / 2 {
+            self.streak = 0;
+            return false;
+        }
+
+        self.streak = self.streak.saturating_add(1);
+        return self.streak == SLOW_FRAME_STREAK;
+    }
+}
@@ -0,0 +1,763 @@
+/**
+ * Command-line Space Invaders, personal introduction to systems-level programming with Rust.
+ * @author Maxylan (https://github.com/Maxylan)
+ * @license MIT
+ */
+use std::{borrow::Cow, env, io, io::Write};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use super::entities::Bounded as _;
+use super::{colors, entities, inspector, FrameRate, GameState, Phase, HudPosition, Pacer, RateDisplay, POPUP_FRAMES};
+
+/** How the renderer gets rid of the previous frame. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenMode {
+    /** Move the cursor home and draw over the previous frame with ANSI escapes, no flicker. */
+    Ansi,
+    /** Wipe the screen with `clearscreen` before every frame, for terminals without ANSI support. */
+    Clear,
+}
+
+impl ScreenMode {
+    /** Use ANSI repositioning unless the terminal is known not to understand it. */
+    pub fn detect() -> Self {
+        return match env::var("TERM") {
+            Ok(term) if term == "dumb" => ScreenMode::Clear,
+            _ => ScreenMode::Ansi,
+        };
+    }
+}
+
+/** What came of presenting a frame, see `Renderer::present`. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Presented {
+    Drawn,
+    /** Drawn, raising a warning for the first time (see `Renderer::warning`). */
+    Warned,
+    /** Whatever was reading the frames went away (a closed pipe), there's no one left to draw for. */
+    Closed,
+}
+
+/**
+ * Presents composed frames to the terminal (or any other writer), managing the screen itself.
+ * Should clearing the screen fail in `ScreenMode::Clear`, frames are still drawn, the failure only
+ * being reported once (see `warning`) rather than on every frame.
+ */
+pub struct Renderer<W: Write> {
+    out: W,
+    mode: ScreenMode,
+    clear: fn() -> Result<(), clearscreen::Error>,
+    warning: Option<String>,
+    /** Dim every other row, see `scanlines`. */
+    crt: bool,
+}
+
+impl<W: Write> Renderer<W> {
+    pub fn new(out: W, mode: ScreenMode) -> Self {
+        return Renderer {
+            out,
+            mode,
+            clear: clearscreen::clear,
+            warning: None,
+            crt: false,
+        };
+    }
+    /** Draw frames with a CRT-style scanline effect (see `scanlines`). */
+    pub fn with_crt(mut self, crt: bool) -> Self {
+        self.crt = crt;
+        return self;
+    }
+    /** Clear the screen with `clear` rather than `clearscreen::clear` (in `ScreenMode::Clear`), one that fails, say. */
+    #[cfg(test)]
+    pub fn with_clear(mut self, clear: fn() -> Result<(), clearscreen::Error>) -> Self {
+        self.clear = clear;
+        return self;
+    }
+    /** The one-time warning raised when clearing the screen failed, if it did. */
+    pub fn warning(&self) -> Option<&str> {
+        return self.warning.as_deref();
+    }
+    /** Write an escape sequence (a window title, for example), flushed along with the next frame. */
+    pub fn queue(&mut self, sequence: &str) -> io::Result<()> {
+        return write!(self.out, "{sequence}");
+    }
+    /**
+     * Draw a composed frame over the previous one, only failing on errors writing the frame itself.
+     * A broken pipe isn't one, it's reported as `Presented::Closed` for the game to shut down cleanly.
+     */
+    pub fn present(&mut self, frame: &str) -> io::Result<Presented> {
+        return match self.draw(frame) {
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(Presented::Closed),
+            Ok(true) => Ok(Presented::Warned),
+            Ok(false) => Ok(Presented::Drawn),
+            Err(e) => Err(e),
+        };
+    }
+    /** Draw the frame, returning `Ok(true)` the (first) time a warning is raised. */
+    fn draw(&mut self, frame: &str) -> io::Result<bool> {
+        let mut warned = false;
+        match self.mode {
+            ScreenMode::Ansi => write!(self.out, "\x1b[H")?,
+            ScreenMode::Clear => {
+                if let Err(e) = (self.clear)() {
+                    if self.warning.is_none() {
+                        self.warning = Some(format!("Cought an error calling 'clearscreen::clear()', {e}"));
+                        warned = true;
+                    }
+
+                    // Might not clear anything, but costs nothing to try.
+                    write!(self.out, "\x1b[H\x1b[2J")?;
+                }
+            }
+        }
+
+        if self.crt {
+            write!(self.out, "{}", scanlines(frame))?;
+        } else {
+            write!(self.out, "{frame}")?;
+        }
+        if self.mode == ScreenMode::Ansi {
+            write!(self.out, "\x1b[J")?; // Clear whatever's left below the frame (after a resize, for example).
+        }
+
+        self.out.flush()?;
+        return Ok(warned);
+    }
+}
+
+/**
+ * Dim every other row of a composed frame (the odd ones), for the look of an old CRT.
+ * Colors reset the terminal's attributes, so the dim is restored after every reset on a dimmed row.
+ */
+pub fn scanlines(frame: &str) -> String {
+    let mut dimmed = String::with_capacity(frame.len() * 2);
+    for (index, row) in frame.split("\r\n").enumerate() {
+        if index > 0 {
+            dimmed += "\r\n";
+        }
+        if index % 2 == 0 {
+            dimmed += row;
+            continue;
+        }
+
+        dimmed += colors::DIM;
+        dimmed += &row.replace(colors::RESET, &format!("{}{}", colors::RESET, colors::DIM));
+        dimmed += colors::UNDIM;
+    }
+
+    return dimmed;
+}
+
+/** The terminal's window (tab) title, showing score and wave. Only rewritten when either changed. */
+#[derive(Debug, Default)]
+pub struct WindowTitle {
+    /** Score and wave currently in the title. */
+    shown: Option<(u32, u16)>,
+}
+
+impl WindowTitle {
+    /** The OSC sequence setting the title to `score` and `wave`, `None` if it already shows them. */
+    pub fn update(&mut self, score: u32, wave: u16) -> Option<String> {
+        if self.shown == Some((score, wave)) {
+            return None;
+        }
+
+        self.shown = Some((score, wave));
+        return Some(format!("\x1b]0;CLI-nvaders | Score: {score} | Wave: {wave}\x07"));
+    }
+}
+
+/**
+ * The HUD's status line, cached between refreshes so it's only recomposed every few presented frames
+ * (`hud_rate` times per second) rather than every frame, keeping a jittery framerate readout from flickering.
+ * The play area is still drawn every frame.
+ */
+#[derive(Debug)]
+pub struct Hud {
+    /** Presented frames between two refreshes. */
+    interval: u32,
+    /** Presented frames since the latest refresh. */
+    frames: u32,
+    status: Option<String>,
+}
+
+impl Hud {
+    /** Refresh `hud_rate` times per second when drawing at `frame_rate`, `None` refreshing every frame. */
+    pub fn new(frame_rate: FrameRate, hud_rate: Option<u8>) -> Self {
+        let interval = match hud_rate {
+            Some(rate) if rate > 0 => (frame_rate.per_second() / rate as u32).max(1),
+            _ => 1,
+        };
+
+        return Hud { interval, frames: 0, status: None };
+    }
+    /** Status line for the next presented frame, recomposed with `compose` when it's due (or was never composed). */
+    pub fn status(&mut self, compose: impl FnOnce() -> String) -> &str {
+        if self.frames == 0 || self.status.is_none() {
+            self.status = Some(compose());
+        }
+
+        self.frames = (self.frames + 1) % self.interval;
+        return self.status.as_deref().unwrap_or_default();
+    }
+}
+
+/**
+ * A play row being drawn, along with its colors, kept between frames so rows are drawn into the same
+ * allocation rather than a fresh one each (see `fill`), as are the grid they're composed into and the rows
+ * painted from it (see `paint`). Also remembers whether the terminal outside of the arena was blanked already,
+ * so it's only written when it has to be (see `render`).
+ */
+#[derive(Debug, Default)]
+pub struct RowBuffer {
+    pub line: String,
+    pub tint: Vec<Option<colors::Color>>,
+    /** The play area as last composed (see `compose_grid`), its rows refilled frame after frame. */
+    grid: Grid,
+    /** Rows of `grid` as last painted for the terminal, repainted frame after frame. */
+    painted: Vec<String>,
+    /** Terminal and arena size (`rows`, `cols` of each) the margin was last blanked at, `None` if it may be dirty. */
+    blanked: Option<(u16, u16, u16, u16)>,
+}
+
+impl RowBuffer {
+    /** Reset the row to `cols` cells of `background` without any tint, only allocating should it not fit already. */
+    pub fn fill(&mut self, background: char, cols: usize) {
+        self.line.clear();
+        self.line.extend(std::iter::repeat_n(background, cols));
+        self.tint.clear();
+        self.tint.resize(cols, None);
+    }
+    /** Paint every row of the grid for the terminal, at the given color `depth`. */
+    pub fn paint(&mut self, depth: colors::ColorDepth) {
+        let rows = self.grid.rows();
+        if self.painted.len() < rows {
+            self.painted.resize_with(rows, String::new);
+        }
+        for (index, painted) in self.painted[..rows].iter_mut().enumerate() {
+            self.grid.paint_row(index, depth, painted);
+        }
+    }
+    /** Address and capacity of every buffer frames are drawn into, for tests to check they're reused. */
+    #[cfg(test)]
+    pub fn allocations(&self) -> Vec<(usize, usize)> {
+        let mut allocations = vec![(self.line.as_ptr() as usize, self.line.capacity()), (self.tint.as_ptr() as usize, self.tint.capacity())];
+        allocations.push((self.grid.cells.as_ptr() as usize, self.grid.cells.capacity()));
+        allocations.extend(self.grid.cells.iter().map(|cells| (cells.as_ptr() as usize, cells.capacity())));
+        allocations.push((self.painted.as_ptr() as usize, self.painted.capacity()));
+        allocations.extend(self.painted.iter().map(|painted| (painted.as_ptr() as usize, painted.capacity())));
+        return allocations;
+    }
+}
+
+/** A single cell of the play area, its glyph and color (`None` being the terminal's own). */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    /** `'\0'` for the second cell of a wide glyph, covered by the one before it. */
+    pub glyph: char,
+    pub color: Option<colors::Color>,
+}
+
+/**
+ * The play area as composed for a frame, one `Cell` per terminal cell, so it can be inspected as is
+ * rather than parsed back out of escape sequences. The terminal renderer only serializes it (see `paint_row`).
+ */
+#[derive(Debug, Clone, Default)]
+pub struct Grid {
+    /** Terminal row of the grid's first row, the HUD may be above it. */
+    top: u16,
+    /** Rows of the frame, the first of `cells`. Any past them are left over from a taller frame, to be refilled. */
+    rows: usize,
+    cells: Vec<Vec<Cell>>,
+}
+
+impl Grid {
+    /** Rows of the grid. */
+    pub fn rows(&self) -> usize {
+        return self.rows;
+    }
+    /** Cell at the given terminal `row` and `col`, `None` outside of the play area. */
+    #[cfg(test)]
+    pub fn cell(&self, row: u16, col: u16) -> Option<&Cell> {
+        return self.cells[..self.rows].get(row.checked_sub(self.top)? as usize)?.get(col as usize);
+    }
+    /** Empty the grid for a frame starting at terminal row `top`, holding on to its rows to refill them. */
+    fn clear(&mut self, top: u16) {
+        self.top = top;
+        self.rows = 0;
+    }
+    /** Append a drawn row (`line`, colored by `tint`), splitting it into cells. */
+    fn push(&mut self, line: &str, tint: &[Option<colors::Color>]) {
+        if self.rows == self.cells.len() {
+            self.cells.push(Vec::with_capacity(tint.len()));
+        }
+
+        let cells = &mut self.cells[self.rows];
+        cells.clear();
+        for glyph in line.chars() {
+            let color = tint.get(cells.len()).copied().flatten();
+            cells.push(Cell { glyph, color });
+            if glyph.width() == Some(2) {
+                cells.push(Cell { glyph: '\0', color });
+            }
+        }
+
+        self.rows += 1;
+    }
+    /** Paint the `index`th row of the grid for the terminal onto `painted`, replacing whatever it held. */
+    pub fn paint_row(&self, index: usize, depth: colors::ColorDepth, painted: &mut String) {
+        painted.clear();
+        if let Some(cells) = self.cells[..self.rows].get(index) {
+            colors::paint(cells.iter().filter(|cell| cell.glyph != '\0').map(|cell| (cell.glyph, cell.color)), depth, painted);
+        }
+    }
+}
+
+/** Compose the play area of `state` into the grid of `rows`, drawing every row through it. */
+pub fn compose_grid<'a>(state: &GameState, rows: &'a mut RowBuffer) -> &'a Grid {
+    let cols = state.size.cols as usize;
+    let vignette = vignette_width(state);
+    rows.grid.clear(state.play_top());
+
+    let mut current_row = state.play_top();
+    while current_row < state.play_bottom() {
+        rows.fill(state.args.background_char, cols);
+        for layer in DRAW_ORDER {
+            draw_layer(layer, current_row, vignette, state, &mut rows.line, &mut rows.tint);
+        }
+
+        rows.grid.push(&rows.line, &rows.tint);
+        current_row += 1;
+    }
+
+    return &rows.grid;
+}
+
+/** Widest (in cells) the slowdown vignette gets, on each side of the play area. */
+const VIGNETTE_WIDTH: u16 = 3;
+
+/** Width of the vignette framing the play area during a slowdown, fading (narrowing) as it runs out. */
+fn vignette_width(state: &GameState) -> u16 {
+    return match state.slowdown {
+        0 => 0_u16,
+        frames => ((frames as u32 * VIGNETTE_WIDTH as u32).div_ceil(state.slowdown_duration().max(1) as u32)) as u16,
+    };
+}
+
+/**
+ * Layers of the play area. Rows are drawn one layer at a time, back to front (see `DRAW_ORDER`),
+ * so whatever is in the foreground always wins a shared cell. The HUD is composed separately, on its own rows.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layer {
+    Vignette,
+    Stars,
+    Aliens,
+    Projectiles,
+    Player,
+    /** Score popups, in front of every entity. */
+    Popups,
+    /** The inspector's cursor, while it's open. */
+    Cursor,
+}
+
+/** Back to front. */
+const DRAW_ORDER: [Layer; 7] = [
+    Layer::Vignette,
+    Layer::Stars,
+    Layer::Aliens,
+    Layer::Projectiles,
+    Layer::Player,
+    Layer::Popups,
+    Layer::Cursor,
+];
+
+/**
+ * Draw a single layer of `row` onto `line`, along with its colors onto `tint`.
+ * Layers without colors of their own clear the tint of the cells they draw over.
+ */
+fn draw_layer(
+    layer: Layer,
+    row: u16,
+    vignette: u16,
+    state: &GameState,
+    line: &mut String,
+    tint: &mut [Option<colors::Color>],
+) {
+    let cols = tint.len();
+    let clear_tint = |tint: &mut [Option<colors::Color>], col: u16, width: u16| {
+        let start = (col as usize).min(cols);
+        let end = ((col + width) as usize).min(cols);
+        tint[start..end].fill(None);
+    };
+
+    match layer {
+        Layer::Vignette => {
+            for offset in 0..vignette.min(state.size.cols >> 1) {
+                replace_at(line, state.glyphs.vignette, offset);
+                replace_at(line, state.glyphs.vignette, state.size.cols - 1 - offset);
+            }
+        }
+        Layer::Stars if !state.args.stars => {}
+        Layer::Stars => {
+            for star in state.falling_stars.iter().filter(|star| star.pos.row == row) {
+                replace_at(line, star.entity, star.pos.col);
+            }
+        }
+        Layer::Aliens => {
+            let hue = if state.args.wave_colors { colors::wave_shift(state.wave) } else { 0_f32 };
+            for alien in state.enemies.iter().filter(|alien| alien.pos.row == row) {
+                let fragment = state.glyphs.fragment.to_string();
+                let sprite = match (alien.is_shielded(), &state.sprites) {
+                    _ if alien.fragment => fragment.as_str(),
+                    (true, _) => state.glyphs.shielded.as_str(),
+                    (false, Some(pack)) => pack.sprite(alien.rank, state.marches),
+                    (false, None) => state.glyphs.alien.as_str(),
+                };
+                draw_sprite(line, sprite, alien.pos.col);
+
+                if !state.args.no_color {
+                    let color = state.theme().alien_color(alien.pos.row, state.play_top(), state.player_row()).hue_rotate(hue);
+                    tint[alien.bounds().span(cols)].fill(Some(color));
+                }
+            }
+
+            if let Some(ufo) = state.ufo.as_ref().filter(|ufo| ufo.pos.row == row) {
+                draw_sprite(line, &state.glyphs.ufo, ufo.pos.col);
+
+                if !state.args.no_color {
+                    tint[ufo.bounds().span(cols)].fill(Some(state.theme().ufo.hue_rotate(hue)));
+                }
+            }
+        }
+        Layer::Projectiles => {
+            for projectile in state.projectiles.iter().filter(|projectile| projectile.pos.row == row) {
+                let glyph = match projectile.owner {
+                    entities::Owner::Player => state.glyphs.projectile,
+                    entities::Owner::Enemy => state.glyphs.enemy_shot,
+                };
+
+                replace_at(line, glyph, projectile.pos.col);
+                clear_tint(tint, projectile.pos.col, 1);
+            }
+        }
+        Layer::Player => {
+            if row == state.player_row() {
+                draw_sprite(line, &state.glyphs.player, state.player.pos.col);
+                clear_tint(tint, state.player.pos.col, state.player.width);
+            }
+        }
+        Layer::Cursor => {
+            let Some(cursor) = state.inspector.map(|inspector| inspector.cursor).filter(|cursor| cursor.row == row) else {
+                return;
+            };
+
+            // Mark empty cells, occupied ones are only tinted so what's under the cursor stays visible.
+            if state.entity_at(cursor).is_none() {
+                replace_at(line, '+', cursor.col);
+            }
+            if let Some(cell) = tint.get_mut(cursor.col as usize) {
+                *cell = Some(colors::CURSOR);
+            }
+        }
+        Layer::Popups => {
+            for popup in state.popups.iter().filter(|popup| popup.pos.row == row) {
+                let text = popup.text();
+                let col = popup.pos.col.saturating_sub(text.width() as u16 >> 1);
+                draw_sprite(line, &text, col);
+
+                let start = (col as usize).min(cols);
+                let end = (col as usize + text.width()).min(cols);
+                if state.args.no_color {
+                    tint[start..end].fill(None);
+                } else {
+                    let faded = 1_f32 - popup.frames_left as f32 / POPUP_FRAMES as f32;
+                    tint[start..end].fill(Some(state.theme().popup.lerp(state.theme().popup_faded, faded)));
+                }
+            }
+        }
+    }
+}
+
+/** The HUD's status line: rates (see `RateDisplay`), score, lives and wave, along with anything else worth flagging. */
+pub fn status_line(frame_rate: u16, state: &GameState) -> String {
+    let margin = state.terminal.cols.saturating_sub(state.size.cols);
+    let wave = match state.args.total_waves {
+        Some(total) => format!("{}/{total}", state.wave),
+        None => state.wave.to_string(),
+    };
+
+    let sim_rate = Pacer::rate(state.args.sim_rate);
+    let mut status = match (state.args.show_debug, state.args.rate_display) {
+        (false, _) => String::new(),
+        (true, RateDisplay::Render) => format!("Framerate: {frame_rate} | "),
+        (true, RateDisplay::Sim) => format!("Sim rate: {sim_rate} | "),
+        (true, RateDisplay::Both) => format!("Sim rate: {sim_rate} | Framerate: {frame_rate} | "),
+    };
+    status += &format!("Score: {} | Lives: {} | Wave: {wave}", state.score, state.lives);
+    if let Some(frames) = state.time_left() {
+        status += &format!(" | Time: {}s", frames.div_ceil(Pacer::rate(state.args.sim_rate) as u64));
+    }
+    if state.args.invincible {
+        status += " | GOD MODE";
+    }
+    if state.args.hardcore {
+        status += " | HARDCORE";
+    }
+    if state.inspector.is_some() && margin < inspector::PANEL_WIDTH {
+        status += " | INSPECTOR (widen terminal)";
+    }
+    if state.slowdown > 0 {
+        let seconds = (state.slowdown as u32).div_ceil(Pacer::rate(state.args.sim_rate));
+        status += &format!(" | BULLET TIME {seconds}s");
+    }
+
+    return status;
+}
+
+/**
+ * Compose a full frame as a string, one line per terminal row, `status` being the HUD's status line (see `status_line`).
+ * Play rows are drawn in `rows`, which is best kept around from one frame to the next.
+ */
+pub fn render(status: &str, state: &GameState, rows: &mut RowBuffer) -> String {
+    let cols = state.size.cols as usize;
+
+    if !state.is_playable() {
+        let cols = state.terminal.cols as usize;
+        let prompt = [
+            String::from("Terminal too small!"),
+            format!("Current: {}x{}", state.terminal.cols, state.terminal.rows),
+            format!("Required: {}x{}", state.args.min_cols, state.args.min_rows),
+        ];
+
+        let mut lines: Vec<String> = vec![];
+        for row in 0..state.terminal.rows as usize {
+            lines.push(match prompt.get(row) {
+                Some(text) => right_pad(text.clone(), cols),
+                None => " ".repeat(cols),
+            });
+        }
+
+        rows.blanked = None;
+        return lines.join("\r\n");
+    }
+
+    // Columns of the terminal right of the arena, see `Viewport`.
+    let margin = state.terminal.cols.saturating_sub(state.size.cols) as usize;
+
+    // Play rows are borrowed from `rows` as painted, only copied should anything be written over them.
+    compose_grid(state, rows);
+    rows.paint(state.args.color_depth);
+    let mut lines: Vec<Cow<str>> = rows.painted[..rows.grid.rows()].iter().map(|row| Cow::Borrowed(row.as_str())).collect();
+
+    // HUD - Debugging / Messaging, separated from the play area by a divider when there's room.
+    let mut hud: Vec<Cow<str>> = vec![Cow::Owned(right_pad(status.to_string(), cols))];
+    if state.hud_rows() > 2 {
+        let headlines: Vec<&str> = state.ticker.iter().map(String::as_str).collect();
+        hud.push(Cow::Owned(right_pad(headlines.join("  "), cols)));
+    }
+    if state.hud_rows() > 1 {
+        hud.push(Cow::Owned(state.glyphs.divider.to_string().repeat(cols)));
+    }
+
+    match state.args.hud_position {
+        HudPosition::Top => {
+            hud.append(&mut lines);
+            lines = hud;
+        }
+        HudPosition::Bottom => {
+            hud.reverse();
+            lines.append(&mut hud);
+        }
+    }
+
+    let sim_rate = Pacer::rate(state.args.sim_rate);
+    let banner: Vec<String> = match state.phase {
+        Phase::Title => {
+            let mut banner = vec![String::from("CLI-NVADERS"), String::new(), String::from("Press SPACE to start")];
+            if state.saved.is_some() {
+                banner.push(String::from("Press C to continue"));
+            }
+            if let Some(leaderboard) = &state.leaderboard {
+                banner.push(String::new());
+                banner.append(&mut leaderboard.lines());
+            }
+            banner
+        }
+        Phase::Countdown(frames) => match (frames as u32).div_ceil(sim_rate) - 1 {
+            0 => vec![String::from("GO!")],
+            seconds => vec![seconds.to_string()],
+        },
+        Phase::Playing | Phase::Quit => vec![],
+        Phase::WaveCleared(_) => vec![format!("WAVE {} CLEARED", state.wave), format!("BONUS +{}", state.wave_bonus)],
+        Phase::Paused(menu) => {
+            let mut banner = vec![String::from("PAUSED"), String::new()];
+            banner.append(&mut menu.lines());
+            banner
+        }
+        Phase::GameOver | Phase::Won => {
+            let title = match state.phase {
+                Phase::Won => "YOU WON THE CAMPAIGN",
+                _ => "GAME OVER",
+            };
+
+            let mut banner = vec![String::from(title), String::new()];
+            banner.append(&mut state.stats.summary(sim_rate));
+            match (&state.initials, &state.leaderboard) {
+                (Some(entry), _) => {
+                    banner.push(String::new());
+                    banner.append(&mut entry.lines());
+                }
+                (None, Some(leaderboard)) => {
+                    banner.push(String::new());
+                    banner.append(&mut leaderboard.lines());
+                }
+                (None, None) => {}
+            }
+            banner
+        }
+    };
+
+    if !banner.is_empty() {
+        let start_at = (lines.len().saturating_sub(banner.len())) >> 1;
+        for (offset, text) in banner.into_iter().enumerate() {
+            if let Some(line) = lines.get_mut(start_at + offset) {
+                let indent = (cols.saturating_sub(text.width()) >> 1) as u16;
+                *line = Cow::Owned(right_pad(left_pad(indent, text, cols as u16), cols));
+            }
+        }
+    }
+
+    // Blank out whatever part of the terminal the arena doesn't cover (see `Viewport`),
+    // where the inspector's panel goes when there's room for it. Left blank, it's only written once
+    // (until the arena or terminal is resized), the renderer clearing whatever's below the frame itself.
+    // Any other background may be wiped by that clear, so it's written every frame.
+    let panel = match state.inspector {
+        Some(inspector) if margin >= inspector::PANEL_WIDTH as usize => inspector.lines(state),
+        _ => vec![],
+    };
+    let geometry = (state.terminal.rows, state.terminal.cols, state.size.rows, state.size.cols);
+    let blank = rows.blanked != Some(geometry) || state.args.background_char != ' ';
+    if margin > 0 {
+        for (index, line) in lines.iter_mut().enumerate() {
+            match panel.get(index) {
+                Some(text) => line.to_mut().push_str(&right_pad(format!(" {text}"), margin)),
+                None if blank => line.to_mut().extend(std::iter::repeat_n(state.args.background_char, margin)),
+                None => {}
+            }
+        }
+    }
+    while blank && lines.len() < state.terminal.rows as usize {
+        lines.push(Cow::Owned(state.args.background_char.to_string().repeat(state.terminal.cols as usize)));
+    }
+    // The panel (or any other background) leaves the margin dirty once it's gone.
+    rows.blanked = if panel.is_empty() && state.args.background_char == ' ' { Some(geometry) } else { None };
+
+    // Raw mode doesn't translate '\n' into a carriage return + line feed.
+    return lines.join("\r\n");
+}
+
+/**
+ * Draw a (multi-cell) sprite onto a row starting at column `index`, clipping anything past the row's end.
+ */
+pub fn draw_sprite(content: &mut String, sprite: &str, index: u16) {
+    let cols = content.width();
+    let mut col = index as usize;
+    for character in sprite.chars() {
+        if col >= cols {
+            break;
+        }
+
+        replace_at(content, character, col as u16);
+        col += character.width().unwrap_or(0);
+    }
+}
+
+/**
+ * Replace whatever occupies terminal cell `index` with `character`, keeping the row's visual width.
+ * A wide (two-cell) glyph swallows the cell after it, replacing a wide glyph with a narrow one pads with a space.
+ */
+pub fn replace_at(content: &mut String, character: char, index: u16) {
+    let index = index as usize;
+    let mut cell = 0_usize;
+    let mut cells = content.char_indices().peekable();
+    while let Some((i, c)) = cells.next() {
+        let width = c.width().unwrap_or(0);
+        if width == 0 || cell + width <= index {
+            cell += width;
+            continue;
+        }
+
+        let mut replacement = character.to_string();
+        let mut end = i + c.len_utf8();
+        let new_width = character.width().unwrap_or(0);
+        if new_width > width {
+            if let Some((next, n)) = cells.next() {
+                end = next + n.len_utf8();
+                if n.width().unwrap_or(0) > 1 {
+                    replacement.push(' ');
+                }
+            }
+        } else if new_width < width {
+            replacement.push(' ');
+        }
+
+        content.replace_range(i..end, &replacement);
+        return;
+    }
+}
+
+/**
+ * Pad-out the string's (visual) width to fill out the remaining cells of a row.
+ */
+pub fn right_pad(mut string_content: String, length: usize) -> String {
+    let width: usize = string_content.width();
+
+    if width == length {
+        return string_content;
+    } else if width > length {
+        // Cut at the last glyph that still fits, padding where a wide glyph would straddle the edge.
+        let mut cells = 0_usize;
+        let mut cut = string_content.len();
+        for (i, c) in string_content.char_indices() {
+            let glyph = c.width().unwrap_or(0);
+            if cells + glyph > length {
+                cut = i;
+                break;
+            }
+
+            cells += glyph;
+        }
+
+        string_content.truncate(cut);
+        string_content += &" ".repeat(length - cells);
+        return string_content;
+    };
+
+    string_content += &" ".repeat(length - width);
+    return string_content;
+}
+
+/**
+ * ..I don't need no NPM Package!
+ */
+pub fn left_pad(start_index: u16, mut string_content: String, length: u16) -> String {
+    if start_index >= length {
+        return vec![" "; length as usize].join("");
+    };
+
+    let line: String = match start_index {
+        0 => String::new(),
+        // _ => vec![" "; start_index as usize].join(""),
+        _ => " ".repeat(start_index.into())
+    };
+
+    // Never spill past the end of the row.
+    let width = start_index as usize + string_content.width();
+    string_content.insert_str(0, &line);
+    if width > length as usize {
+        return right_pad(string_content, length as usize);
+    }
+
+    return string_content;
+}
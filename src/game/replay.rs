@@ -0,0 +1,120 @@
+/**
+ * Command-line Space Invaders, personal introduction to systems-level programming with Rust.
+ * @author Maxylan (https://github.com/Maxylan)
+ * @license MIT
+ */
+use std::fmt;
+
+use super::Action;
+
+/** The game played by `--demo`, recorded off the AI at the default settings. */
+pub const DEMO: &str = include_str!("demo.replay");
+
+/**
+ * A recorded game: the seed and (forced) terminal size it was played at, along with the actions of every step.
+ * Played back at the same settings, the simulation being deterministic, it plays out the same game again.
+ *
+ * Stored as text, a `seed` and `size` (`<cols>x<rows>`) header followed by a line per run of identical steps,
+ * a repeat count and that step's actions (`L`eft, `R`ight, `F`ire, `-` for none), e.g: `12 -` or `1 LF`.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replay {
+    pub seed: u64,
+    pub rows: u16,
+    pub cols: u16,
+    pub steps: Vec<Vec<Action>>,
+}
+
+impl Replay {
+    /** The bundled demo, see `DEMO`. */
+    pub fn demo() -> Result<Self, String> {
+        return Replay::parse(DEMO).map_err(|e| format!("Couldn't load the bundled demo, {e}"));
+    }
+    /** Parse a replay in its text format, see `Replay`. */
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+        let mut header = |key: &str| -> Result<String, String> {
+            return match lines.next().and_then(|line| line.strip_prefix(key)) {
+                Some(value) => Ok(value.trim().to_string()),
+                None => Err(format!("expected a '{}' header", key.trim_end_matches('='))),
+            };
+        };
+
+        let seed = header("seed=")?;
+        let seed = seed.parse::<u64>().map_err(|_| format!("invalid seed '{seed}'"))?;
+        let size = header("size=")?;
+        let (cols, rows) = match size.split_once('x').map(|(cols, rows)| (cols.parse::<u16>(), rows.parse::<u16>())) {
+            Some((Ok(cols), Ok(rows))) => (cols, rows),
+            _ => return Err(format!("invalid size '{size}', expected '<cols>x<rows>'")),
+        };
+
+        let mut steps: Vec<Vec<Action>> = vec![];
+        for line in lines {
+            let Some((count, codes)) = line.split_once(' ') else {
+                return Err(format!("invalid step '{line}', expected '<count> <actions>'"));
+            };
+            let count = count.parse::<usize>().map_err(|_| format!("invalid count in step '{line}'"))?;
+            let actions = codes.trim().chars().filter(|code| *code != '-').map(action).collect::<Result<Vec<Action>, String>>()?;
+            steps.extend(std::iter::repeat_n(actions, count));
+        }
+
+        return Ok(Replay { seed, rows, cols, steps });
+    }
+}
+
+impl fmt::Display for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "seed={}", self.seed)?;
+        writeln!(f, "size={}x{}", self.cols, self.rows)?;
+
+        let mut index = 0_usize;
+        while index < self.steps.len() {
+            let step = &self.steps[index];
+            let count = self.steps[index..].iter().take_while(|other| *other == step).count();
+            let codes: String = step.iter().filter_map(code).collect();
+            writeln!(f, "{count} {}", if codes.is_empty() { "-" } else { codes.as_str() })?;
+            index += count;
+        }
+
+        return Ok(());
+    }
+}
+
+/** Steps through a replay, one step of actions at a time. */
+#[derive(Debug, Clone)]
+pub struct Playback {
+    replay: Replay,
+    step: usize,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        return Playback { replay, step: 0 };
+    }
+    /** Actions of the next step, `None` once the recording has run out. */
+    pub fn next_step(&mut self) -> Option<Vec<Action>> {
+        let actions = self.replay.steps.get(self.step).cloned();
+        self.step += 1;
+        return actions;
+    }
+}
+
+/** The action recorded as `code`. */
+fn action(code: char) -> Result<Action, String> {
+    return match code {
+        'L' => Ok(Action::MoveLeft),
+        'R' => Ok(Action::MoveRight),
+        'F' => Ok(Action::Fire),
+        other => Err(format!("unknown action '{other}'")),
+    };
+}
+
+/** How an action is recorded, `None` for what isn't recorded (only gameplay is). */
+fn code(action: &Action) -> Option<char> {
+    return match action {
+        Action::MoveLeft => Some('L'),
+        Action::MoveRight => Some('R'),
+        Action::Fire => Some('F'),
+        _ => None,
+    };
+}
@@ -0,0 +1,243 @@
+/**
+ * Command-line Space Invaders, personal introduction to systems-level programming with Rust.
+ * @author Maxylan (https://github.com/Maxylan)
+ * @license MIT
+ */
+use std::{fs, io, path::Path};
+
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+use termsize::Size;
+use unicode_width::UnicodeWidthStr;
+
+use super::{cli, difficulty, entities, entities::Bounded as _, stats, Arguments, FormationPhase, GameState, Phase};
+
+/** Version of the save format, saves of any other version are discarded rather than continued. */
+pub const VERSION: u32 = 2;
+
+/** Just the version of a save, read before the rest so another version's save is refused whatever its shape. */
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+/** `termsize::Size`, as saved. */
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Size")]
+struct SizeDef {
+    rows: u16,
+    cols: u16,
+}
+
+/** Everything the simulation plays out from, as saved (see `write`). */
+#[derive(Serialize, Deserialize)]
+struct Save {
+    version: u32,
+    /** The RNG's seed, stream and position in it. */
+    rng: ChaCha12Rng,
+    #[serde(with = "SizeDef")]
+    arena: Size,
+    phase: Phase,
+    frame: u64,
+    clock: u64,
+    score: u32,
+    lives: u8,
+    extra_life_threshold: u32,
+    stats: stats::SessionStats,
+    wave: u16,
+    wave_params: difficulty::WaveParams,
+    wave_size: usize,
+    wave_bonus: u32,
+    marches: u64,
+    last_alien_id: u32,
+    enemy_direction: i8,
+    descent: u16,
+    formation: FormationPhase,
+    next_ufo_direction: i8,
+    /** Column of the ship, its row following from the arena. */
+    player: u16,
+    last_shot: Option<u64>,
+    slowdown: u16,
+    enemies: Vec<entities::Alien>,
+    respawns: Vec<(u16, entities::Alien)>,
+    projectiles: Vec<entities::Projectile>,
+    ufo: Option<entities::Ufo>,
+    popups: Vec<entities::ScorePopup>,
+    /** Cells of the falling stars, drawn with whatever glyph the game is continued with. */
+    stars: Vec<entities::Pos>,
+}
+
+/**
+ * A game in progress, written on quitting so it can be continued from the title screen next time (`--save`).
+ * Everything the simulation plays out from is saved, the RNG's position in its stream included, so a continued
+ * game plays out exactly as it would have. Settings aren't, they're whatever the game is continued with.
+ *
+ * Stored as JSON, through `serde`, the `version` along with every field of a `Save`.
+ */
+pub fn write(state: &GameState) -> String {
+    let save = Save {
+        version: VERSION,
+        rng: state.rng.clone(),
+        arena: Size { rows: state.size.rows, cols: state.size.cols },
+        // A game saved from the pause menu is continued on it anyway (see `GameState::continue_saved`).
+        phase: match state.phase {
+            Phase::Countdown(frames) => Phase::Countdown(frames),
+            Phase::WaveCleared(frames) => Phase::WaveCleared(frames),
+            _ => Phase::Playing,
+        },
+        frame: state.frame,
+        clock: state.clock,
+        score: state.score,
+        lives: state.lives,
+        extra_life_threshold: state.extra_life_threshold,
+        stats: state.stats.clone(),
+        wave: state.wave,
+        wave_params: state.wave_params,
+        wave_size: state.wave_size,
+        wave_bonus: state.wave_bonus,
+        marches: state.marches,
+        last_alien_id: state.last_alien_id,
+        enemy_direction: state.enemy_direction,
+        descent: state.descent,
+        formation: state.formation,
+        next_ufo_direction: state.next_ufo_direction,
+        player: state.player.pos.col,
+        last_shot: state.last_shot,
+        slowdown: state.slowdown,
+        enemies: state.enemies.clone(),
+        respawns: state.respawns.clone(),
+        projectiles: state.projectiles.clone(),
+        ufo: state.ufo.clone(),
+        popups: state.popups.clone(),
+        stars: state.falling_stars.iter().map(|star| star.pos).collect(),
+    };
+
+    return serde_json::to_string_pretty(&save).expect("a save serializes to JSON");
+}
+
+/**
+ * Continue the game saved in `text` (see `write`), replacing whatever `state` was up to. Nothing is touched
+ * unless the whole save could be read and makes sense (see `check`), a save of another `VERSION` being refused outright.
+ */
+pub fn restore(state: &mut GameState, text: &str) -> Result<(), String> {
+    let header: Header = serde_json::from_str(text).map_err(|e| format!("expected a 'version' header, {e}"))?;
+    if header.version != VERSION {
+        return Err(format!("saved by another version ({}, expected {VERSION})", header.version));
+    }
+    let save: Save = serde_json::from_str(text).map_err(|e| format!("unreadable save, {e}"))?;
+    check(&save)?;
+
+    // All of it read, the game carries on from the save.
+    state.rng = save.rng;
+    state.size = save.arena;
+    state.phase = save.phase;
+    state.frame = save.frame;
+    state.clock = save.clock;
+    state.score = save.score;
+    state.lives = save.lives;
+    state.extra_life_threshold = save.extra_life_threshold;
+    state.stats = save.stats;
+    state.events.clear();
+    state.ticker.clear();
+    state.wave = save.wave;
+    state.wave_params = save.wave_params;
+    state.wave_size = save.wave_size;
+    state.wave_bonus = save.wave_bonus;
+    state.marches = save.marches;
+    state.enemies = save.enemies;
+    state.last_alien_id = save.last_alien_id;
+    state.respawns = save.respawns;
+    state.enemy_direction = save.enemy_direction;
+    state.descent = save.descent;
+    state.formation = save.formation;
+    state.projectiles = save.projectiles;
+    state.ufo = save.ufo;
+    state.popups = save.popups;
+    state.falling_stars = save.stars.into_iter().map(|pos| entities::FallingStar { pos, entity: state.glyphs.star }).collect();
+    state.next_ufo_direction = save.next_ufo_direction;
+    state.player.width = state.glyphs.player.width() as u16;
+    state.player.pos = entities::Pos::new(state.player_row(), save.player);
+    state.last_shot = save.last_shot;
+    state.slowdown = save.slowdown;
+    state.initials = None;
+
+    return Ok(());
+}
+
+/**
+ * Whether the game in `save` can be played on from, its phase being one a game is saved in and everything
+ * in it lying within its arena. Describes the first value that can't be, should there be one.
+ */
+fn check(save: &Save) -> Result<(), String> {
+    let arena = &save.arena;
+    if arena.rows < cli::MIN_ROWS || arena.cols < cli::MIN_COLS {
+        return Err(format!("invalid arena {}x{}, expected at least {}x{}", arena.cols, arena.rows, cli::MIN_COLS, cli::MIN_ROWS));
+    }
+    match save.phase {
+        Phase::Playing => {}
+        Phase::Countdown(frames) | Phase::WaveCleared(frames) if frames > 0 => {}
+        phase => return Err(format!("invalid phase {phase:?}")),
+    }
+
+    // Whether a block of cells lies all within the arena.
+    let inside = |bounds: entities::Rect| {
+        return bounds.w > 0 && bounds.h > 0 && bounds.row as u32 + bounds.h as u32 <= arena.rows as u32 && bounds.col as u32 + bounds.w as u32 <= arena.cols as u32;
+    };
+    for alien in save.enemies.iter().chain(save.respawns.iter().map(|(_, alien)| alien)) {
+        if alien.hp == 0 || !inside(alien.bounds()) {
+            return Err(format!("invalid alien {} at {},{} with {} hp", alien.id, alien.pos.row, alien.pos.col, alien.hp));
+        }
+    }
+    if let Some(shot) = save.projectiles.iter().find(|shot| !inside(shot.swept())) {
+        return Err(format!("invalid shot at {},{}", shot.pos.row, shot.pos.col));
+    }
+    if let Some(ufo) = save.ufo.as_ref().filter(|ufo| !inside(ufo.bounds())) {
+        return Err(format!("invalid ufo at {},{}", ufo.pos.row, ufo.pos.col));
+    }
+    if let Some(popup) = save.popups.iter().find(|popup| !popup.pos.in_bounds(arena)) {
+        return Err(format!("invalid popup at {},{}", popup.pos.row, popup.pos.col));
+    }
+    if let Some(star) = save.stars.iter().find(|star| !star.in_bounds(arena)) {
+        return Err(format!("invalid star at {},{}", star.row, star.col));
+    }
+    if save.player >= arena.cols {
+        return Err(format!("invalid player column {}", save.player));
+    }
+
+    return Ok(());
+}
+
+/**
+ * The save at `path`, unless there's none. A save that can't be continued (another version's, most likely,
+ * or one that doesn't make sense) is discarded, deleting the file, rather than failing the game over it.
+ */
+pub fn load(path: &Path) -> io::Result<Option<String>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+
+    if let Err(e) = restore(&mut GameState::new(Arguments::default()), &text) {
+        eprintln!("Warning! Discarding the saved game '{}', {e}.", path.display());
+        fs::remove_file(path)?;
+        return Ok(None);
+    }
+
+    return Ok(Some(text));
+}
+
+/** Save the game at `path` should it be in progress, otherwise delete whatever save it was continued from. */
+pub fn settle(state: &GameState, path: &Path) -> io::Result<()> {
+    if state.in_progress() {
+        return fs::write(path, write(state));
+    }
+    if matches!(state.phase, Phase::GameOver | Phase::Won) {
+        return match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+
+    return Ok(());
+}
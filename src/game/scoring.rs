@@ -0,0 +1,32 @@
+/**
+ * Command-line Space Invaders, personal introduction to systems-level programming with Rust.
+ * @author Maxylan (https://github.com/Maxylan)
+ * @license MIT
+ */
+/** What everything is worth, consulted wherever points are awarded. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScoreConfig {
+    /** Points for shooting down an alien (fragments included). */
+    pub alien_points: u32,
+    /** Points for shooting down the bonus UFO. */
+    pub ufo_points: u32,
+    /** End-of-wave bonus, per life left. */
+    pub wave_bonus_per_life: u32,
+}
+
+impl Default for ScoreConfig {
+    fn default() -> Self {
+        return ScoreConfig {
+            alien_points: 10,
+            ufo_points: 100,
+            wave_bonus_per_life: 50,
+        };
+    }
+}
+
+impl ScoreConfig {
+    /** Bonus for clearing a wave with `lives` left. */
+    pub fn wave_bonus(&self, lives: u8) -> u32 {
+        return self.wave_bonus_per_life.saturating_mul(lives as u32);
+    }
+}
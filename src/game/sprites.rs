@@ -0,0 +1,55 @@
+/**
+ * Command-line Space Invaders, personal introduction to systems-level programming with Rust.
+ * @author Maxylan (https://github.com/Maxylan)
+ * @license MIT
+ */
+use unicode_width::UnicodeWidthStr;
+
+/** Alien art packs to pick from (`--alien-skin <name>`). */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Skin {
+    Crabs,
+    Ufos,
+    Letters,
+}
+
+impl Skin {
+    pub fn parse(name: &str) -> Option<Skin> {
+        return match name {
+            "crabs" => Some(Skin::Crabs),
+            "ufos" => Some(Skin::Ufos),
+            "letters" => Some(Skin::Letters),
+            _ => None,
+        };
+    }
+}
+
+/**
+ * Two-frame alien sprites, one pair per formation row (cycling should the formation be deeper than the pack).
+ * The frames alternate every time the formation marches. Pure ASCII, so they're safe on any terminal.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpritePack {
+    rows: Vec<[&'static str; 2]>,
+}
+
+impl SpritePack {
+    pub fn new(skin: Skin) -> Self {
+        let rows = match skin {
+            Skin::Crabs => vec![["{@}", "}@{"], ["/M\\", "\\M/"], ["(w)", ")w("]],
+            Skin::Ufos => vec![["<o>", "<O>"], ["(=)", "[=]"], ["-o-", "=o="]],
+            Skin::Letters => vec![["A-A", "a-a"], ["B=B", "b=b"], ["C~C", "c~c"]],
+        };
+
+        return SpritePack { rows };
+    }
+    /** Sprite of an alien from formation row `rank`, on animation `frame` (only its parity matters). */
+    pub fn sprite(&self, rank: u16, frame: u64) -> &'static str {
+        let frames = self.rows[rank as usize % self.rows.len()];
+        return frames[(frame % 2) as usize];
+    }
+    /** Widest sprite of the pack, in terminal cells. */
+    pub fn width(&self) -> usize {
+        return self.rows.iter().flatten().map(|sprite| sprite.width()).max().unwrap_or(1);
+    }
+}
@@ -0,0 +1,87 @@
+/**
+ * Command-line Space Invaders, personal introduction to systems-level programming with Rust.
+ * @author Maxylan (https://github.com/Maxylan)
+ * @license MIT
+ */
+use std::{fs, io, io::Write, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use super::events::Event;
+
+/** Statistics of a single run (session), kept up to date from the event bus. */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionStats {
+    pub shots_fired: u32,
+    pub hits: u32,
+    pub aliens_killed: u32,
+    /** Frames (simulation steps) survived. */
+    pub frames: u64,
+    /** Longest streak of consecutive hits. */
+    pub max_combo: u32,
+    /** Current streak of consecutive hits. */
+    pub combo: u32,
+}
+
+impl SessionStats {
+    /** Update the stats with an event from the event bus. */
+    pub fn record(&mut self, event: &Event) {
+        match event {
+            Event::ShotFired => self.shots_fired += 1,
+            Event::ShotMissed => self.combo = 0,
+            Event::AlienCracked { .. } | Event::UfoDestroyed { .. } => {
+                self.hits += 1;
+                self.combo += 1;
+                self.max_combo = self.max_combo.max(self.combo);
+            }
+            Event::AlienDestroyed { .. } => {
+                self.hits += 1;
+                self.aliens_killed += 1;
+                self.combo += 1;
+                self.max_combo = self.max_combo.max(self.combo);
+            }
+            Event::WaveCleared { .. }
+            | Event::PlayerHit
+            | Event::ExtraLife
+            | Event::HighScore { .. }
+            | Event::GameOver
+            | Event::CampaignWon => {}
+        }
+    }
+    /** Percentage of fired shots that hit something. */
+    pub fn accuracy(&self) -> f32 {
+        if self.shots_fired == 0 {
+            return 0_f32;
+        }
+
+        return self.hits as f32 / self.shots_fired as f32 * 100_f32;
+    }
+    /** Seconds survived, at the given simulation rate (steps/sec). */
+    pub fn seconds_survived(&self, sim_rate: u32) -> u64 {
+        return self.frames / sim_rate.max(1) as u64;
+    }
+    /** Human-readable summary lines, as shown on the game-over screen. */
+    pub fn summary(&self, sim_rate: u32) -> Vec<String> {
+        return vec![
+            format!("Shots fired: {}", self.shots_fired),
+            format!("Hits: {} ({:.1}%)", self.hits, self.accuracy()),
+            format!("Aliens killed: {}", self.aliens_killed),
+            format!("Time survived: {}s", self.seconds_survived(sim_rate)),
+            format!("Max combo: {}", self.max_combo),
+        ];
+    }
+    /** Append the stats as a single comma-separated line to the log file at `path`. */
+    pub fn append_to(&self, path: &Path) -> io::Result<()> {
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+        return writeln!(
+            file,
+            "{},{},{},{},{:.1},{}",
+            self.shots_fired,
+            self.hits,
+            self.aliens_killed,
+            self.frames,
+            self.accuracy(),
+            self.max_combo
+        );
+    }
+}
@@ -0,0 +1,87 @@
+/**
+ * Command-line Space Invaders, personal introduction to systems-level programming with Rust.
+ * @author Maxylan (https://github.com/Maxylan)
+ * @license MIT
+ */
+use termsize::Size;
+
+/** Where the terminal's size comes from, so it can be faked (in tests, for example). */
+pub trait SizeProvider: std::fmt::Debug {
+    /** Size of the terminal, `None` if it couldn't be determined. */
+    fn size(&self) -> Option<Size>;
+}
+
+/** The actual terminal's size, through `termsize`. */
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TerminalSize;
+
+impl SizeProvider for TerminalSize {
+    fn size(&self) -> Option<Size> {
+        return termsize::get();
+    }
+}
+
+/** Rows the arena grows by, every `arena_growth` points (or when scaled up). */
+const GROW_ROWS: u16 = 2;
+/** Columns the arena grows by, every `arena_growth` points (or when scaled up). */
+const GROW_COLS: u16 = 8;
+
+/**
+ * Dimensions of the arena the game is played in, anchored to the top-left corner of the terminal.
+ * Normally the whole terminal, but it may start small and grow with score instead (`arena_growth`),
+ * and the player may scale it up / down at runtime (`Ctrl +`/`Ctrl -`).
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    pub rows: u16,
+    pub cols: u16,
+}
+
+impl Viewport {
+    /** The whole terminal. */
+    pub fn full(terminal: &Size) -> Self {
+        return Viewport { rows: terminal.rows, cols: terminal.cols };
+    }
+    /** An arena of `min_rows`x`min_cols`, grown `steps` times over, but never past the terminal. */
+    pub fn grown(terminal: &Size, min_rows: u16, min_cols: u16, steps: u32) -> Self {
+        let steps = steps.min(u16::MAX as u32) as u16;
+        return Viewport {
+            rows: min_rows.saturating_add(steps.saturating_mul(GROW_ROWS)).min(terminal.rows),
+            cols: min_cols.saturating_add(steps.saturating_mul(GROW_COLS)).min(terminal.cols),
+        };
+    }
+    /**
+     * Grow (or for negative `steps`, shrink) the viewport by `steps` increments, as the player scales the arena
+     * at runtime. Never smaller than `min_rows`x`min_cols`, nor larger than the terminal.
+     */
+    pub fn scaled(self, terminal: &Size, steps: i16, min_rows: u16, min_cols: u16) -> Self {
+        let scale = |value: u16, step: u16, min: u16, max: u16| {
+            let scaled = value as i32 + steps as i32 * step as i32;
+            return scaled.clamp(min.min(max) as i32, max as i32) as u16;
+        };
+
+        return Viewport {
+            rows: scale(self.rows, GROW_ROWS, min_rows, terminal.rows),
+            cols: scale(self.cols, GROW_COLS, min_cols, terminal.cols),
+        };
+    }
+    /**
+     * Narrow the viewport to leave `cols` free on the right of the terminal,
+     * unless that would make it narrower than `min_cols`.
+     */
+    pub fn reserve_cols(self, terminal: &Size, cols: u16, min_cols: u16) -> Self {
+        let available = terminal.cols.saturating_sub(cols);
+        if available < min_cols {
+            return self;
+        }
+
+        return Viewport { rows: self.rows, cols: self.cols.min(available) };
+    }
+    pub fn size(&self) -> Size {
+        return Size { rows: self.rows, cols: self.cols };
+    }
+    /** Whether the viewport covers exactly the given size. */
+    pub fn matches(&self, size: &Size) -> bool {
+        return self.rows == size.rows && self.cols == size.cols;
+    }
+}
@@ -1,15 +1,43 @@
+#![allow(clippy::needless_return)]
 /**
  * Command-line Space Invaders, personal introduction to systems-level programming with Rust.
  * @author Maxylan (https://github.com/Maxylan)
  * @license MIT
  */
+use std::{io, io::Write, sync::atomic::AtomicBool};
+
 mod game;
 
 fn main() {
-    game::start(game::Arguments {
-        frame_rate: Some(8),
-        bullet_time: 2,
-        enemy_time: 8,
-        panic_on_errors: true,
-    });
+    // Bad flags fail like any other bad configuration (see `GameError::Config`).
+    let args = match game::Arguments::resolve(std::env::args().skip(1), std::env::vars()) {
+        Ok(args) => args,
+        Err(error_message) => fail(game::GameError::Config(error_message)),
+    };
+
+    if let Err(error) = args.validate() {
+        fail(game::GameError::Config(error.to_string()));
+    }
+
+    // Nothing requests a shutdown from outside yet, the player quits from within the game.
+    let stop = AtomicBool::new(false);
+    let benchmark = args.benchmark.is_some();
+    let exit_code = match game::start(args, &stop) {
+        Ok(outcome) => {
+            if !benchmark {
+                // Benchmarks print a report of their own. Ignoring a closed pipe, as `start()` does.
+                let _ = writeln!(io::stdout(), "{outcome}");
+            }
+            outcome.exit_code()
+        }
+        Err(error) => fail(error),
+    };
+
+    std::process::exit(exit_code);
+}
+
+/** Report the error and exit with its code. */
+fn fail(error: game::GameError) -> ! {
+    eprintln!("Error! {}", error);
+    std::process::exit(error.exit_code());
 }